@@ -0,0 +1,235 @@
+//! Baseline timings for PageRank, word counting and in-memory indexing, and the memory
+//! word counts take, so optimizations can be checked against numbers. Run with
+//! `cargo bench`, or `cargo bench -- pagerank` to run only the benchmarks whose name
+//! contains `pagerank`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::{HashMap, HashSet};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use scraper::{Html, Selector};
+use search_enginge::crawler::datascraper::{count_word_ids, count_words_with, ScrapeResult};
+use search_enginge::crawler::jsonld::StructuredData;
+use search_enginge::indexer::algorithms::pagerank::{calculate_pagerank, LinkGraph};
+use search_enginge::indexer::{index_pages, IndexBackend, IndexerConfig};
+use search_enginge::text::interner::{SharedInterner, WordCounts, WordInterner};
+use search_enginge::text::normalize::WordOptions;
+use search_enginge::text::Stopwords;
+
+/// Each benchmark runs for at least this long after one warm-up run.
+const MEASURE_FOR: Duration = Duration::from_secs(2);
+
+/// Bytes currently allocated on the heap, kept by [`CountingAllocator`].
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting the bytes allocated so memory can be measured too.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const WORDS: &[&str] = &[
+    "ownership", "borrowing", "lifetime", "compiler", "memory", "thread", "async", "runtime",
+    "garden", "tomatoes", "soil", "summer", "Rust", "Python", "index", "search", "query", "ranking",
+];
+
+/// Deterministic pseudo-random numbers, so every run measures the same input.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+fn text(rng: &mut Lcg, words: usize) -> String {
+    (0..words).map(|_| WORDS[rng.next(WORDS.len())]).collect::<Vec<_>>().join(" ")
+}
+
+/// A lowercase word of letters only, distinct for every `n`.
+fn synthetic_word(mut n: usize) -> String {
+    let mut word = String::from("w");
+    loop {
+        word.push((b'a' + (n % 26) as u8) as char);
+        n /= 26;
+        if n == 0 {
+            return word + "x";
+        }
+    }
+}
+
+/// `pages` texts of `words` words each out of a vocabulary of `vocabulary`, rank `r` drawn
+/// about as often as `1/r` like words of natural text (Zipf's law).
+fn zipf_texts(pages: usize, words: usize, vocabulary: usize) -> Vec<String> {
+    let vocab: Vec<String> = (0..vocabulary).map(synthetic_word).collect();
+    let mut rng = Lcg(11);
+    (0..pages)
+        .map(|_| {
+            (0..words)
+                .map(|_| {
+                    let u = rng.next(1 << 20) as f64 / f64::from(1 << 20);
+                    let rank = ((vocabulary as f64).ln() * u).exp() as usize - 1;
+                    vocab[rank.min(vocabulary - 1)].as_str()
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// `pages` pages with `links_per_page` links each, a few of them to pages outside the graph.
+fn link_graph(pages: usize, links_per_page: usize) -> LinkGraph {
+    let mut rng = Lcg(pages as u64);
+    (0..pages)
+        .map(|n| {
+            let links: HashSet<String> = (0..links_per_page)
+                .map(|_| match rng.next(10) {
+                    0 => format!("https://external.test/{}", rng.next(pages)),
+                    _ => format!("https://site.test/{}", rng.next(pages)),
+                })
+                .collect();
+            (format!("https://site.test/{}", n), links)
+        })
+        .collect()
+}
+
+fn sample_html(rng: &mut Lcg) -> String {
+    let paragraphs: String = (0..40).map(|_| format!("<p>{} <a href=\"/x\">{}</a></p>", text(rng, 40), text(rng, 3))).collect();
+    format!(
+        "<html><head><title>{}</title></head><body><nav>{}</nav><main>{}</main><footer>{}</footer></body></html>",
+        text(rng, 6), text(rng, 20), paragraphs, text(rng, 30)
+    )
+}
+
+fn pages(count: usize) -> Vec<ScrapeResult> {
+    let mut rng = Lcg(7);
+    (0..count)
+        .map(|n| ScrapeResult {
+            url: format!("https://site.test/{}", n),
+            title: Some(text(&mut rng, 6)),
+            body_text: text(&mut rng, 300),
+            links: (0..10).map(|_| format!("https://site.test/{}", rng.next(count))).collect(),
+            internal_links: 10,
+            external_links: 0,
+            anchors: Vec::new(),
+            is_partial: false,
+            language: "eng".to_string(),
+            word_counts: None,
+            structured: StructuredData::default(),
+            no_index: false,
+            no_follow: false,
+            likely_404: false,
+            bytes_downloaded: 0,
+            favicon: None,
+            status: Some(200),
+            redirected_from: None,
+            fetched_at: None,
+        })
+        .collect()
+}
+
+/// Runs `f` repeatedly and prints the mean and fastest time per run. `setup` builds each
+/// run's input outside the measurement.
+fn bench<I, O>(filter: Option<&str>, name: &str, mut setup: impl FnMut() -> I, mut f: impl FnMut(I) -> O) {
+    if filter.is_some_and(|filter| !name.contains(filter)) {
+        return;
+    }
+    black_box(f(setup()));
+    let (mut runs, mut total, mut fastest) = (0u32, Duration::ZERO, Duration::MAX);
+    while total < MEASURE_FOR {
+        let input = setup();
+        let started = Instant::now();
+        black_box(f(input));
+        let elapsed = started.elapsed();
+        runs += 1;
+        total += elapsed;
+        fastest = fastest.min(elapsed);
+    }
+    println!("{:<32} {:>12.3?} mean {:>12.3?} min ({} runs)", name, total / runs, fastest, runs);
+}
+
+/// Prints the heap still held by what `build` returns.
+fn memory<O>(filter: Option<&str>, name: &str, build: impl FnOnce() -> O) {
+    if filter.is_some_and(|filter| !name.contains(filter)) {
+        return;
+    }
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let kept = black_box(build());
+    let bytes = ALLOCATED.load(Ordering::Relaxed).saturating_sub(before);
+    drop(kept);
+    println!("{:<32} {:>9.1} MB live", name, bytes as f64 / 1_000_000.0);
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`; anything else is a name filter
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+    let filter = filter.as_deref();
+
+    for pages in [1_000, 10_000, 25_000] {
+        let graph = link_graph(pages, 20);
+        bench(filter, &format!("pagerank/{}_pages", pages), || &graph, calculate_pagerank);
+    }
+
+    let mut rng = Lcg(42);
+    let html: Vec<String> = (0..20).map(|_| sample_html(&mut rng)).collect();
+    let body = Selector::parse("body").expect("valid selector");
+    let texts: Vec<String> = html
+        .iter()
+        .map(|page| Html::parse_document(page).select(&body).flat_map(|e| e.text()).collect::<Vec<_>>().join(" "))
+        .collect();
+    let stopwords = Stopwords::english();
+    bench(filter, "html/parse_and_extract_text", || &html, |html| {
+        html.iter().map(|page| Html::parse_document(page).select(&body).flat_map(|e| e.text()).count()).sum::<usize>()
+    });
+    bench(filter, "words/count_words", || &texts, |texts| {
+        texts.iter().map(|text| count_words_with(text, &stopwords, WordOptions::default()).len()).sum::<usize>()
+    });
+    bench(filter, "words/count_word_ids_interned", || &texts, |texts| {
+        let mut interner = WordInterner::default();
+        texts.iter().map(|text| count_word_ids(text, &stopwords, WordOptions::default(), &mut interner).len()).sum::<usize>()
+    });
+
+    // The counts a crawl of 500 pages keeps, as one map per page or as ids of one interner
+    let crawl = zipf_texts(500, 2_000, 50_000);
+    memory(filter, "memory/word_counts_500_pages", || {
+        crawl.iter().map(|text| count_words_with(text, &stopwords, WordOptions::default())).collect::<Vec<HashMap<String, u32>>>()
+    });
+    memory(filter, "memory/word_counts_interned_500", || {
+        let interner = SharedInterner::new();
+        crawl.iter().map(|text| WordCounts::count(text, &stopwords, WordOptions::default(), &interner)).collect::<Vec<_>>()
+    });
+
+    let config = IndexerConfig { min_words: 1, pagerank: false, show_progress: false, ..Default::default() };
+    for count in [1_000, 5_000] {
+        bench(filter, &format!("index/{}_docs_in_memory", count), || pages(count), |pages| {
+            index_pages(pages, &IndexBackend::Memory, &config).expect("in-memory indexing succeeds")
+        });
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use url::Url;
+
+use super::datascraper::ScrapeResult;
+
+/// Domains and terms a crawl stays away from, e.g. for a family-safe index.
+///
+/// Nothing is blocked unless lists are given: the crate ships no lists of its own, they
+/// are read from files with [`read_list`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blocklist {
+    /// Lowercase hosts; subdomains of each are blocked too.
+    domains: HashSet<String>,
+    /// Lowercase terms as space-separated words, see [`words_of`].
+    terms: Vec<String>,
+}
+
+impl Blocklist {
+    /// Never fetches nor follows links to these hosts or their subdomains.
+    pub fn with_domains<S: AsRef<str>>(mut self, domains: &[S]) -> Self {
+        self.domains.extend(domains.iter().map(|d| d.as_ref().trim().trim_start_matches('.').to_lowercase()));
+        self
+    }
+
+    /// Doesn't keep pages whose title or body contains one of these words or phrases,
+    /// matched case-insensitively on whole words.
+    pub fn with_terms<S: AsRef<str>>(mut self, terms: &[S]) -> Self {
+        self.terms.extend(terms.iter().map(|t| words_of(t.as_ref())).filter(|t| !t.is_empty()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty() && self.terms.is_empty()
+    }
+
+    /// Whether `url`'s host or one of its parent domains is blocked. URLs without a host
+    /// aren't.
+    pub fn blocks_url(&self, url: &str) -> bool {
+        if self.domains.is_empty() {
+            return false;
+        }
+        let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_lowercase)) else { return false };
+        let mut domain = host.as_str();
+        loop {
+            if self.domains.contains(domain) {
+                return true;
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// The first blocked term in `page`'s title or body.
+    pub fn blocked_term(&self, page: &ScrapeResult) -> Option<&str> {
+        if self.terms.is_empty() {
+            return None;
+        }
+        let text = format!(" {} {} ", words_of(page.title.as_deref().unwrap_or_default()), words_of(&page.body_text));
+        self.terms.iter().find(|term| text.contains(&format!(" {} ", term))).map(String::as_str)
+    }
+}
+
+/// Lowercase words of `text` separated by single spaces, so phrases match regardless of
+/// punctuation and line breaks.
+fn words_of(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Entries of a domain or term list: one per line, skipping blank lines and `#` comments.
+/// Lines in hosts-file format (`0.0.0.0 example.com`), as many published domain lists
+/// use, give the domain.
+pub fn read_list(path: &Path) -> io::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let entries = content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once(char::is_whitespace) {
+            Some((address, domain)) if address.parse::<std::net::IpAddr>().is_ok() => domain.trim().to_string(),
+            _ => line.to_string(),
+        })
+        .collect();
+    Ok(entries)
+}
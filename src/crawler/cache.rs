@@ -0,0 +1,77 @@
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::datascraper::ScrapeResult;
+
+/// HTTP validators from a previous fetch, sent back as `If-None-Match` and
+/// `If-Modified-Since` on the next one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedMeta {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CachedMeta {
+    /// Reads `ETag` and `Last-Modified`, or `None` if the response has neither (it can't
+    /// be revalidated, so there's no point caching it).
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let meta = Self { etag: header(ETAG), last_modified: header(LAST_MODIFIED) };
+        (meta.etag.is_some() || meta.last_modified.is_some()).then_some(meta)
+    }
+}
+
+/// A cached page: its validators and the result parsed from its last full response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPage {
+    pub meta: CachedMeta,
+    pub result: ScrapeResult,
+}
+
+/// Per-URL store of [`CachedPage`]s, shared by every clone of a scraper.
+///
+/// On a `304 Not Modified` the stored result is reused as is, skipping the download and
+/// the HTML parsing. Persist it between runs with [`save`](Self::save) and
+/// [`load`](Self::load) to make recrawls incremental.
+#[derive(Debug, Clone, Default)]
+pub struct HttpCache(Arc<Mutex<HashMap<String, CachedPage>>>);
+
+impl HttpCache {
+    /// Reads a cache written by [`save`](Self::save). A missing file gives an empty cache.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = std::fs::File::open(path)?;
+        let pages: HashMap<String, CachedPage> = serde_json::from_reader(io::BufReader::new(file))?;
+        Ok(Self(Arc::new(Mutex::new(pages))))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let pages = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        serde_json::to_writer(io::BufWriter::new(file), &*pages)?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, url: &str) -> Option<CachedPage> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).get(url).cloned()
+    }
+
+    pub fn insert(&self, url: &str, meta: CachedMeta, result: &ScrapeResult) {
+        let page = CachedPage { meta, result: result.clone() };
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).insert(url.to_string(), page);
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use super::blocklist::{self, Blocklist};
+use super::datascraper::LinkSource;
+use super::focus::{TopicFocus, DEFAULT_MIN_TOPIC_SCORE};
+use super::language::{LanguageDetector, LanguageFilter, DEFAULT_MIN_CONFIDENCE};
+
+/// File read by `index` when no `--config` is given, if it exists.
+pub const DEFAULT_CONFIG_FILE: &str = "crawl_config.toml";
+
+/// Where and how to crawl, usually loaded from a `crawl_config.toml` such as:
+///
+/// ```toml
+/// seeds = ["https://www.rust-lang.org/"]
+/// limit = 1000
+/// concurrency = 10
+/// allowed_domains = ["rust-lang.org"]
+/// denied_paths = ["/login", "/search"]
+/// path_prefix_filter = ["https://www.rust-lang.org/learn"]
+/// user_agent = "MyCrawler/1.0"
+/// politeness_delay_ms = 500
+/// max_requests_per_second = 50
+/// max_duration = "30m"
+/// request_timeout = "20s"
+/// languages = ["eng", "deu"]
+/// language_confidence = 0.7
+/// only_languages = ["en", "de"]
+/// keep_unknown_language = false
+/// link_sources = ["pagination"]
+/// focus_keywords = ["rust", "cargo", "borrow"]
+/// focus_min_score = 0.05
+/// danger_accept_invalid_certs = false
+/// respect_env_proxy = true
+/// record_redirects = true
+/// blocked_domains_file = "blocked_domains.txt"
+/// blocked_terms_file = "blocked_terms.txt"
+///
+/// [headers]
+/// Accept-Language = "en"
+/// ```
+///
+/// Every field is optional and falls back to the defaults used without a file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlConfig {
+    /// URLs the crawl starts from.
+    pub seeds: Vec<String>,
+    /// Total number of pages to fetch.
+    pub limit: usize,
+    /// Fetches in flight at the same time.
+    pub concurrency: usize,
+    /// Only follow links to these hosts and their subdomains. Empty allows every host.
+    pub allowed_domains: Vec<String>,
+    /// Never follow links whose path starts with one of these prefixes.
+    pub denied_paths: Vec<String>,
+    /// Only follow links starting with one of these URL prefixes, e.g.
+    /// `https://docs.rs/tokio/`. Empty allows every link.
+    pub path_prefix_filter: Vec<String>,
+    /// Replaces the scraper's default User-Agent.
+    pub user_agent: Option<String>,
+    /// Minimum pause between two requests to the same host.
+    pub politeness_delay_ms: u64,
+    /// Cap on requests per second over all hosts together. `None` doesn't limit the rate.
+    pub max_requests_per_second: Option<f64>,
+    /// Wall-clock budget for the crawl, written like `90s`, `10m` or `2h`.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub max_duration: Option<Duration>,
+    /// Time allowed per page, from connecting until it's parsed. Defaults to the
+    /// scraper's 10 seconds.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub request_timeout: Option<Duration>,
+    /// Extra headers sent with every request.
+    pub headers: BTreeMap<String, String>,
+    /// ISO 639-3 codes language detection may choose from. Empty allows every language.
+    pub languages: Vec<String>,
+    /// Pages detected with less confidence than this (0 to 1) get language "unknown".
+    pub language_confidence: f64,
+    /// Only keep pages in these languages (ISO 639-1 or 639-3), following the links of the
+    /// others. Empty keeps every language.
+    pub only_languages: Vec<String>,
+    /// With [`only_languages`](Self::only_languages), also keep pages whose language
+    /// couldn't be detected. Short pages often can't be.
+    pub keep_unknown_language: bool,
+    /// Follow links from these places as well as `<a href>`: `area`, `pagination`
+    /// (`<link rel="next|prev">`) or `data-href`.
+    pub link_sources: Vec<LinkSource>,
+    /// Topic of a focused crawl, see [`TopicFocus`]. Empty crawls everything alike.
+    pub focus_keywords: Vec<String>,
+    /// Topic score below which a page's links aren't followed in a focused crawl.
+    pub focus_min_score: f64,
+    /// Accept invalid TLS certificates, e.g. self-signed ones on test servers. Insecure,
+    /// see [`ScraperBuilder::danger_accept_invalid_certs`](super::datascraper::ScraperBuilder::danger_accept_invalid_certs).
+    pub danger_accept_invalid_certs: bool,
+    /// Use the proxy configured by `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`, see
+    /// [`ScraperBuilder::respect_env_proxy`](super::datascraper::ScraperBuilder::respect_env_proxy).
+    pub respect_env_proxy: bool,
+    /// Keep the redirect chain of every page, see
+    /// [`ScraperBuilder::record_redirects`](super::datascraper::ScraperBuilder::record_redirects).
+    pub record_redirects: bool,
+    /// File of domains never to fetch, one per line, see [`blocklist::read_list`].
+    pub blocked_domains_file: Option<String>,
+    /// File of words and phrases whose pages aren't kept, one per line.
+    pub blocked_terms_file: Option<String>,
+}
+
+impl Default for CrawlConfig {
+    fn default() -> Self {
+        Self {
+            seeds: [
+                "https://en.wikipedia.org/wiki/Computer_science",
+                "https://www.rust-lang.org/",
+                "https://news.ycombinator.com/",
+                "https://github.com/rust-lang/rust",
+                "https://stackoverflow.com/questions/tagged/rust",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            limit: 500,
+            concurrency: 25,
+            allowed_domains: Vec::new(),
+            denied_paths: Vec::new(),
+            path_prefix_filter: Vec::new(),
+            user_agent: None,
+            politeness_delay_ms: 0,
+            max_requests_per_second: None,
+            max_duration: None,
+            request_timeout: None,
+            headers: BTreeMap::new(),
+            languages: Vec::new(),
+            language_confidence: DEFAULT_MIN_CONFIDENCE,
+            only_languages: Vec::new(),
+            keep_unknown_language: true,
+            link_sources: Vec::new(),
+            focus_keywords: Vec::new(),
+            focus_min_score: DEFAULT_MIN_TOPIC_SCORE,
+            danger_accept_invalid_certs: false,
+            respect_env_proxy: true,
+            record_redirects: false,
+            blocked_domains_file: None,
+            blocked_terms_file: None,
+        }
+    }
+}
+
+impl CrawlConfig {
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Checks settings that would make the crawl do nothing or fail later.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.seeds.is_empty() {
+            return Err("At least one seed URL is required.".to_string());
+        }
+        if let Some(seed) = self.seeds.iter().find(|s| url::Url::parse(s).is_err()) {
+            return Err(format!("Invalid seed URL '{}'.", seed));
+        }
+        if self.limit == 0 {
+            return Err("Page limit must be at least 1.".to_string());
+        }
+        if self.concurrency == 0 {
+            return Err("Concurrency must be at least 1.".to_string());
+        }
+        if let Some(rate) = self.max_requests_per_second
+            && !(rate.is_finite() && rate > 0.0)
+        {
+            return Err(format!("Request rate must be a positive number of requests per second, got {}.", rate));
+        }
+        if self.request_timeout.is_some_and(|t| t.is_zero()) {
+            return Err("Request timeout must be at least 1 second.".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.language_confidence) {
+            return Err(format!("Language confidence must be between 0 and 1, got {}.", self.language_confidence));
+        }
+        if !(0.0..=1.0).contains(&self.focus_min_score) {
+            return Err(format!("Focus score threshold must be between 0 and 1, got {}.", self.focus_min_score));
+        }
+        self.header_map()?;
+        self.language_detector()?;
+        self.language_filter()?;
+        Ok(())
+    }
+
+    /// The [`TopicFocus`] for [`focus_keywords`](Self::focus_keywords), if any.
+    pub fn focus(&self) -> Option<TopicFocus> {
+        (!self.focus_keywords.is_empty())
+            .then(|| TopicFocus::new(self.focus_keywords.clone()).with_min_score(self.focus_min_score))
+    }
+
+    /// Detector honoring [`languages`](Self::languages) and [`language_confidence`](Self::language_confidence).
+    pub fn language_detector(&self) -> Result<LanguageDetector, String> {
+        LanguageDetector::default()
+            .with_min_confidence(self.language_confidence)
+            .with_expected_languages(&self.languages)
+    }
+
+    /// The [`LanguageFilter`] for [`only_languages`](Self::only_languages), if any.
+    pub fn language_filter(&self) -> Result<Option<LanguageFilter>, String> {
+        if self.only_languages.is_empty() {
+            return Ok(None);
+        }
+        LanguageFilter::new(&self.only_languages, self.keep_unknown_language).map(Some)
+    }
+
+    /// The [`Blocklist`] read from [`blocked_domains_file`](Self::blocked_domains_file) and
+    /// [`blocked_terms_file`](Self::blocked_terms_file), empty without them.
+    pub fn blocklist(&self) -> Result<Blocklist, String> {
+        let read = |path: &Option<String>| match path {
+            Some(path) => blocklist::read_list(Path::new(path)).map_err(|e| format!("Failed to read blocklist '{}': {}", path, e)),
+            None => Ok(Vec::new()),
+        };
+        Ok(Blocklist::default().with_domains(&read(&self.blocked_domains_file)?).with_terms(&read(&self.blocked_terms_file)?))
+    }
+
+    /// [`headers`](Self::headers) as a validated header map.
+    pub fn header_map(&self) -> Result<reqwest::header::HeaderMap, String> {
+        super::datascraper::parse_headers(self.headers.iter().map(|(name, value)| (name.as_str(), value.as_str())))
+    }
+}
+
+/// Reads one seed URL per line, skipping blank lines and `#` comments. Malformed URLs
+/// are logged and left out instead of failing the whole file.
+pub fn read_seed_file(path: &Path) -> std::io::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut seeds = Vec::new();
+    for (n, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match url::Url::parse(line) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => seeds.push(line.to_string()),
+            _ => tracing::warn!("{}:{}: skipping malformed seed URL '{}'", path.display(), n + 1, line),
+        }
+    }
+    Ok(seeds)
+}
+
+/// Parses a duration such as `45s`, `10m`, `2h` or a plain number of seconds.
+pub fn parse_duration(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let invalid = || format!("Invalid duration '{}'. Use e.g. 90s, 10m or 2h.", raw);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "" | "s" => Some(number),
+        "m" => number.checked_mul(60),
+        "h" => number.checked_mul(3600),
+        _ => return Err(format!("Invalid duration unit '{}'. Use s, m or h.", unit)),
+    };
+    secs.map(Duration::from_secs).ok_or_else(invalid)
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let raw = Option::<String>::deserialize(deserializer)?;
+    raw.map(|raw| parse_duration(&raw).map_err(serde::de::Error::custom)).transpose()
+}
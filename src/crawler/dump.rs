@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use super::datascraper::ScrapeResult;
+
+/// First line of a dump: the crawl its pages came from, so an index rebuilt from the file
+/// later records that crawl instead of the rebuild.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpHeader {
+    /// Seed URLs of the crawl.
+    pub seeds: Vec<String>,
+    /// When the crawl started, in Unix seconds.
+    pub crawled_at: i64,
+}
+
+/// An NDJSON file of crawled pages, one [`ScrapeResult`] per line after a [`DumpHeader`],
+/// shared by every clone of a crawler.
+///
+/// A crawler given one via [`Crawler::with_page_dump`](super::Crawler::with_page_dump)
+/// writes each page as soon as it's fetched instead of holding it until the crawl ends,
+/// and [`index_dump`](crate::indexer::index_dump) builds the index from the file, now or
+/// in a later run.
+#[derive(Debug, Clone)]
+pub struct PageDump(Arc<Mutex<BufWriter<File>>>);
+
+impl PageDump {
+    /// Creates `path`, replacing any file already there, and writes `header` to it.
+    pub fn create(path: &Path, header: &DumpHeader) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", serde_json::to_string(header)?)?;
+        Ok(Self(Arc::new(Mutex::new(writer))))
+    }
+
+    /// Appends `page` as one line.
+    pub fn write(&self, page: &ScrapeResult) -> io::Result<()> {
+        let line = serde_json::to_string(page)?;
+        let mut writer = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(writer, "{}", line)
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).flush()
+    }
+}
+
+/// Reads the pages of a dump written by [`PageDump`] back one at a time, so only the
+/// current line is held in memory. The header and blank lines are skipped.
+pub fn read_pages(path: &Path) -> io::Result<PageReader> {
+    Ok(PageReader { lines: BufReader::new(File::open(path)?).lines(), line: 0 })
+}
+
+/// The [`DumpHeader`] of the dump at `path`. Dumps written before there was one have
+/// none, which is `Ok(None)`.
+pub fn read_header(path: &Path) -> io::Result<Option<DumpHeader>> {
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            return Ok(serde_json::from_str(&line).ok());
+        }
+    }
+    Ok(None)
+}
+
+/// Iterator returned by [`read_pages`]. A line that isn't a page fails with its line number.
+pub struct PageReader {
+    lines: Lines<BufReader<File>>,
+    line: usize,
+}
+
+impl Iterator for PageReader {
+    type Item = io::Result<ScrapeResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            self.line += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // Pages never parse as a header: they have neither of its fields
+            if self.line == 1 && serde_json::from_str::<DumpHeader>(&line).is_ok() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", self.line, e))
+            }));
+        }
+    }
+}
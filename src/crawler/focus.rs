@@ -0,0 +1,87 @@
+use super::datascraper::ScrapeResult;
+use crate::text::interner::{SharedInterner, WordCounts};
+use crate::text::normalize::{words, WordOptions};
+use crate::text::Stopwords;
+
+/// Pages scoring below this are off-topic by default: their links aren't followed.
+pub const DEFAULT_MIN_TOPIC_SCORE: f64 = 0.02;
+/// Frontier priority added per unit of the linking page's topic score. URL structure
+/// only moves priorities by tens, so links from on-topic pages go first.
+pub const TOPIC_PRIORITY_WEIGHT: f64 = 1000.0;
+
+/// How relevant `page` is to `keywords`, from 0 (none of them occur) to 1: the cosine
+/// similarity between the page's word counts (title and body, or
+/// [`ScrapeResult::word_counts`] when the scraper computed them) and the keywords, each
+/// weighted equally. Keywords are matched case-insensitively as whole words, including
+/// ones [`count_words`](super::datascraper::count_words) leaves out like "ai", "go" or "it", which are counted on the text.
+pub fn topic_score(page: &ScrapeResult, keywords: &[String]) -> f64 {
+    if keywords.is_empty() {
+        return 0.0;
+    }
+    let text = format!("{} {}", page.title.as_deref().unwrap_or_default(), page.body_text);
+    let stopwords = Stopwords::english();
+    let computed;
+    let counts: &WordCounts = match &page.word_counts {
+        Some(counts) => counts,
+        None => {
+            computed = WordCounts::count(&text, &stopwords, WordOptions::default(), &SharedInterner::new());
+            &computed
+        }
+    };
+    let mut norm_squared: f64 = counts.ids().iter().map(|&(_, n)| f64::from(n).powi(2)).sum();
+    let mut overlap = 0.0;
+    for keyword in keywords {
+        let keyword = keyword.to_lowercase();
+        let count = if is_counted(&keyword, &stopwords) {
+            counts.get(&keyword).unwrap_or(0)
+        } else {
+            // Not among the counts, so it joins the page's vector here
+            let count = count_whole_word(&text, &keyword);
+            norm_squared += f64::from(count).powi(2);
+            count
+        };
+        overlap += f64::from(count);
+    }
+    if norm_squared == 0.0 {
+        return 0.0;
+    }
+    overlap / (norm_squared.sqrt() * (keywords.len() as f64).sqrt())
+}
+
+/// Whether [`count_words`](super::datascraper::count_words) keeps `keyword` (lowercase) as a word of its own.
+fn is_counted(keyword: &str, stopwords: &Stopwords) -> bool {
+    words(keyword, WordOptions::default()) == [keyword] && !stopwords.contains(keyword)
+}
+
+/// Occurrences of the lowercase `word` in `text` as a whole word.
+fn count_whole_word(text: &str, word: &str) -> u32 {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .filter(|w| w.to_lowercase() == word)
+        .count() as u32
+}
+
+/// Focused crawling: prefer links from pages about `keywords` and don't follow links
+/// from pages scoring below `min_score` (see [`topic_score`]). Seeds are always
+/// followed, so broad starting points like homepages still lead somewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicFocus {
+    pub keywords: Vec<String>,
+    pub min_score: f64,
+}
+
+impl TopicFocus {
+    pub fn new(keywords: Vec<String>) -> Self {
+        Self { keywords, min_score: DEFAULT_MIN_TOPIC_SCORE }
+    }
+
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Priority boost for the links of a page with this topic score, or `None` if they
+    /// shouldn't be followed. `depth` is the page's own distance from a seed.
+    pub fn link_boost(&self, score: f64, depth: usize) -> Option<i64> {
+        (depth == 0 || score >= self.min_score).then_some((score * TOPIC_PRIORITY_WEIGHT) as i64)
+    }
+}
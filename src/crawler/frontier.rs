@@ -0,0 +1,71 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use url::Url;
+
+/// Scores a URL found `depth` links away from a seed. Higher is crawled first.
+pub type PriorityFn = fn(&str, usize) -> i64;
+
+/// Default priority: favours shallow pages (homepages, section roots) over deep
+/// pagination and parameterised URLs.
+pub fn url_priority(url: &str, depth: usize) -> i64 {
+    let Ok(parsed) = Url::parse(url) else {
+        return i64::MIN;
+    };
+
+    let path_depth = parsed
+        .path_segments()
+        .map_or(0, |segments| segments.filter(|s| !s.is_empty()).count());
+    let query_params = parsed.query_pairs().count();
+
+    100 - 10 * path_depth as i64 - 15 * query_params as i64 - 5 * depth as i64
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct QueuedUrl {
+    priority: i64,
+    // Insertion order, so equal priorities are crawled FIFO
+    seq: u64,
+    url: String,
+    depth: usize,
+}
+
+impl Ord for QueuedUrl {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedUrl {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The crawl frontier: URLs waiting to be fetched, highest priority first.
+#[derive(Debug, Default)]
+pub struct Frontier {
+    heap: BinaryHeap<QueuedUrl>,
+    next_seq: u64,
+}
+
+impl Frontier {
+    pub fn push(&mut self, url: String, depth: usize, priority: i64) {
+        self.heap.push(QueuedUrl { priority, seq: self.next_seq, url, depth });
+        self.next_seq += 1;
+    }
+
+    /// Returns the highest priority URL and its depth.
+    pub fn pop(&mut self) -> Option<(String, usize)> {
+        self.heap.pop().map(|q| (q.url, q.depth))
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
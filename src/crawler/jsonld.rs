@@ -0,0 +1,87 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+static JSON_LD_SELECTOR: OnceLock<Option<Selector>> = OnceLock::new();
+
+/// Fields pulled from a page's `<script type="application/ld+json">` blocks.
+/// When several blocks provide a field, the first one wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructuredData {
+    /// Schema.org types, e.g. `Article`, `Product`, `Organization`.
+    pub types: Vec<String>,
+    pub date_published: Option<String>,
+    pub author: Option<String>,
+}
+
+impl StructuredData {
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.date_published.is_none() && self.author.is_none()
+    }
+}
+
+/// Parses every JSON-LD block of the document. Malformed blocks are skipped
+/// rather than failing the scrape.
+pub fn extract(document: &Html) -> StructuredData {
+    let mut data = StructuredData::default();
+    let Some(selector) = JSON_LD_SELECTOR.get_or_init(|| Selector::parse("script[type='application/ld+json']").ok()) else {
+        return data;
+    };
+    for script in document.select(selector) {
+        let raw: String = script.text().collect();
+        let Ok(value) = serde_json::from_str::<Value>(&raw) else {
+            continue;
+        };
+        collect(&value, &mut data);
+    }
+    data
+}
+
+/// Walks a JSON-LD value: either a single node, an array of nodes, or a node with `@graph`.
+fn collect(value: &Value, data: &mut StructuredData) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect(item, data)),
+        Value::Object(node) => {
+            if let Some(graph) = node.get("@graph") {
+                collect(graph, data);
+            }
+
+            match node.get("@type") {
+                Some(Value::String(t)) => push_unique(&mut data.types, t),
+                Some(Value::Array(types)) => types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .for_each(|t| push_unique(&mut data.types, t)),
+                _ => {}
+            }
+
+            if data.date_published.is_none() {
+                data.date_published = node.get("datePublished").and_then(Value::as_str).map(str::to_string);
+            }
+            if data.author.is_none() {
+                data.author = node.get("author").and_then(author_name);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `author` may be a plain name, a `Person` object or a list of either.
+fn author_name(value: &Value) -> Option<String> {
+    match value {
+        Value::String(name) => Some(name.clone()),
+        Value::Object(person) => person.get("name").and_then(Value::as_str).map(str::to_string),
+        Value::Array(authors) => {
+            let names: Vec<String> = authors.iter().filter_map(author_name).collect();
+            (!names.is_empty()).then(|| names.join(", "))
+        }
+        _ => None,
+    }
+}
+
+fn push_unique(types: &mut Vec<String>, t: &str) {
+    if !types.iter().any(|existing| existing == t) {
+        types.push(t.to_string());
+    }
+}
@@ -0,0 +1,111 @@
+use whatlang::{Detector, Lang};
+
+/// Guesses below this confidence are reported as unknown.
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.5;
+
+/// Value of [`ScrapeResult::language`](super::datascraper::ScrapeResult::language) when
+/// no language could be detected confidently.
+pub const UNKNOWN: &str = "unknown";
+
+/// Language detection with a confidence floor.
+///
+/// whatlang always returns its best guess, and on short or mixed-language text that
+/// guess is often wrong. Labeling such pages `unknown` keeps them out of language
+/// filters instead of filing them under a random language. Restricting detection to the
+/// languages a crawl expects also helps: whatlang then only has to tell those apart.
+#[derive(Debug, Clone)]
+pub struct LanguageDetector {
+    detector: Detector,
+    min_confidence: f64,
+}
+
+impl Default for LanguageDetector {
+    fn default() -> Self {
+        Self { detector: Detector::new(), min_confidence: DEFAULT_MIN_CONFIDENCE }
+    }
+}
+
+impl LanguageDetector {
+    /// Reports guesses below `min_confidence` (0 to 1) as unknown.
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Only considers these ISO 639-3 codes, e.g. `["eng", "deu"]`. An empty list considers
+    /// every language. Fails on a code whatlang doesn't know.
+    pub fn with_expected_languages<S: AsRef<str>>(mut self, codes: &[S]) -> Result<Self, String> {
+        if codes.is_empty() {
+            self.detector = Detector::new();
+            return Ok(self);
+        }
+        let languages = codes
+            .iter()
+            .map(|code| {
+                let code = code.as_ref().trim();
+                Lang::from_code(code).ok_or_else(|| format!("Unknown language code '{}'. Use ISO 639-3, e.g. 'eng'.", code))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.detector = Detector::with_allowlist(languages);
+        Ok(self)
+    }
+
+    /// ISO 639-3 code of `text`'s language, or [`UNKNOWN`] if whatlang has no confident guess.
+    pub fn detect(&self, text: &str) -> String {
+        match self.detector.detect(text) {
+            Some(info) if info.confidence() >= self.min_confidence => info.lang().code().to_string(),
+            _ => UNKNOWN.to_string(),
+        }
+    }
+}
+
+/// ISO 639-1 codes of the languages whatlang detects, with their ISO 639-3 equivalent.
+const ISO_639_1: &[(&str, &str)] = &[
+    ("af", "afr"), ("ak", "aka"), ("am", "amh"), ("ar", "ara"), ("az", "aze"), ("be", "bel"), ("bg", "bul"),
+    ("bn", "ben"), ("ca", "cat"), ("cs", "ces"), ("da", "dan"), ("de", "deu"), ("el", "ell"), ("en", "eng"),
+    ("eo", "epo"), ("es", "spa"), ("et", "est"), ("fa", "pes"), ("fi", "fin"), ("fr", "fra"), ("gu", "guj"),
+    ("he", "heb"), ("hi", "hin"), ("hr", "hrv"), ("hu", "hun"), ("hy", "hye"), ("id", "ind"), ("it", "ita"),
+    ("ja", "jpn"), ("jv", "jav"), ("ka", "kat"), ("km", "khm"), ("kn", "kan"), ("ko", "kor"), ("la", "lat"),
+    ("lt", "lit"), ("lv", "lav"), ("mk", "mkd"), ("ml", "mal"), ("mr", "mar"), ("my", "mya"), ("nb", "nob"),
+    ("ne", "nep"), ("nl", "nld"), ("no", "nob"), ("or", "ori"), ("pa", "pan"), ("pl", "pol"), ("pt", "por"),
+    ("ro", "ron"), ("ru", "rus"), ("si", "sin"), ("sk", "slk"), ("sl", "slv"), ("sn", "sna"), ("sr", "srp"),
+    ("sv", "swe"), ("ta", "tam"), ("te", "tel"), ("th", "tha"), ("tk", "tuk"), ("tl", "tgl"), ("tr", "tur"),
+    ("uk", "ukr"), ("ur", "urd"), ("uz", "uzb"), ("vi", "vie"), ("yi", "yid"), ("zh", "cmn"), ("zu", "zul"),
+];
+
+/// The ISO 639-3 code of a language given as ISO 639-1 (`en`) or 639-3 (`eng`), as
+/// [`LanguageDetector::detect`] reports it. Fails on languages whatlang doesn't know.
+pub fn language_code(code: &str) -> Result<String, String> {
+    let code = code.trim().to_lowercase();
+    let code = ISO_639_1.iter().find(|(short, _)| *short == code).map_or(code.as_str(), |&(_, long)| long);
+    match Lang::from_code(code) {
+        Some(lang) => Ok(lang.code().to_string()),
+        None => Err(format!("Unknown language code '{}'. Use ISO 639-1 or 639-3, e.g. 'en' or 'eng'.", code)),
+    }
+}
+
+/// Languages of the pages a crawl keeps. Pages in other languages are still fetched and
+/// their links followed, they're just left out of the results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageFilter {
+    /// ISO 639-3 codes.
+    languages: Vec<String>,
+    keep_unknown: bool,
+}
+
+impl LanguageFilter {
+    /// Keeps pages in one of `codes` (see [`language_code`]) and, if `keep_unknown`,
+    /// pages whose language couldn't be detected.
+    pub fn new<S: AsRef<str>>(codes: &[S], keep_unknown: bool) -> Result<Self, String> {
+        let languages = codes.iter().map(|code| language_code(code.as_ref())).collect::<Result<_, _>>()?;
+        Ok(Self { languages, keep_unknown })
+    }
+
+    /// Whether a page detected as `language` is kept.
+    pub fn allows(&self, language: &str) -> bool {
+        if language == UNKNOWN {
+            return self.keep_unknown;
+        }
+        self.languages.iter().any(|l| l == language)
+    }
+}
@@ -1,94 +1,617 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::task::JoinSet;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, info_span, warn, Instrument};
+use std::time::{Duration, Instant};
+use url::Url;
 
+pub mod blocklist;
+pub mod cache;
+pub mod config;
 // Expose the datascraper module so others can use ScrapeResult if needed
 pub mod datascraper;
-use datascraper::{Scraper, ScrapeResult};
+pub mod dump;
+pub mod focus;
+pub mod frontier;
+pub mod jsonld;
+pub mod language;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod ratelimit;
+pub mod soft404;
+pub mod stats;
+pub mod traps;
+use blocklist::Blocklist;
+use datascraper::{Scraper, ScrapeError, ScrapeResult};
+use dump::PageDump;
+use focus::TopicFocus;
+use language::LanguageFilter;
+use frontier::{Frontier, PriorityFn};
+use ratelimit::RateLimiter;
+use stats::{CrawlStats, SkipReason};
+use traps::TrapDetector;
+
+/// Times a URL answered with `429 Too Many Requests` is retried before it's skipped.
+pub const MAX_RATE_LIMIT_RETRIES: usize = 3;
+/// How long a host is left alone after a 429 without a usable `Retry-After` header.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(30);
+/// Longest wait honoured from a `Retry-After` header; longer ones are cut to this.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(600);
+
+/// Upper bound on queued URLs so link-dense sites can't grow the frontier without limit.
+pub const DEFAULT_MAX_QUEUE_SIZE: usize = 10_000;
 
 #[derive(Clone)]
 pub struct Crawler {
     scraper: Scraper,
+    /// [`url_key`](datascraper::url_key)s of the pages fetched so far.
     visited: Arc<Mutex<HashSet<String>>>,
-    queue: Arc<Mutex<VecDeque<String>>>,
+    queue: Arc<Mutex<Frontier>>,
+    shutdown: Arc<AtomicBool>,
+    max_queue_size: usize,
+    priority_fn: PriorityFn,
+    traps: Arc<Mutex<TrapDetector>>,
+    max_pages_per_domain: Option<usize>,
+    allowed_domains: Vec<String>,
+    denied_paths: Vec<String>,
+    path_prefixes: Vec<String>,
+    politeness_delay: Duration,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    focus: Option<TopicFocus>,
+    show_progress: bool,
+    max_duration: Option<Duration>,
+    keep_partial: bool,
+    dump: Option<PageDump>,
+    language_filter: Option<LanguageFilter>,
+    blocklist: Arc<Blocklist>,
 }
 
 impl Crawler {
     pub fn new(seed_urls: &[&str]) -> Self {
-        let queue: VecDeque<String> = seed_urls.iter().map(|s| s.to_string()).collect();
+        let priority_fn: PriorityFn = frontier::url_priority;
+        let mut queue = Frontier::default();
+        for seed in seed_urls {
+            queue.push(seed.to_string(), 0, priority_fn(seed, 0));
+        }
         Self {
             scraper: Scraper::new(),
             visited: Arc::new(Mutex::new(HashSet::new())),
             queue: Arc::new(Mutex::new(queue)),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            max_queue_size: DEFAULT_MAX_QUEUE_SIZE,
+            priority_fn,
+            traps: Arc::new(Mutex::new(TrapDetector::default())),
+            max_pages_per_domain: None,
+            allowed_domains: Vec::new(),
+            denied_paths: Vec::new(),
+            path_prefixes: Vec::new(),
+            politeness_delay: Duration::ZERO,
+            rate_limiter: None,
+            focus: None,
+            show_progress: true,
+            max_duration: None,
+            keep_partial: false,
+            dump: None,
+            language_filter: None,
+            blocklist: Arc::new(Blocklist::default()),
+        }
+    }
+
+    /// Returns paywalled pages (`is_partial`, metadata text only) instead of dropping them.
+    pub fn with_partial_pages(mut self, keep: bool) -> Self {
+        self.keep_partial = keep;
+        self
+    }
+
+    /// Never fetches URLs on the blocklist's domains nor follows links to them, and drops
+    /// pages containing one of its terms without following their links.
+    pub fn with_blocklist(mut self, blocklist: Blocklist) -> Self {
+        self.blocklist = Arc::new(blocklist);
+        self
+    }
+
+    /// Leaves pages in languages `filter` doesn't allow out of the results. Their links
+    /// are still followed, since pages in other languages often link to wanted ones.
+    pub fn with_language_filter(mut self, filter: Option<LanguageFilter>) -> Self {
+        self.language_filter = filter;
+        self
+    }
+
+    /// Writes every page to `dump` as soon as it's fetched instead of collecting it, so
+    /// memory doesn't grow with the crawl. [`crawl`](Self::crawl) then returns no pages.
+    pub fn with_page_dump(mut self, dump: Option<PageDump>) -> Self {
+        self.dump = dump;
+        self
+    }
+
+    /// Stops starting new fetches once the crawl has run this long. In-flight fetches
+    /// still finish and everything collected is returned.
+    pub fn with_max_duration(mut self, max_duration: Option<Duration>) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// Shows a pages crawled / limit progress bar on stderr (on by default, only drawn on a terminal).
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    /// Only follows links to these hosts or their subdomains (`rust-lang.org` also allows
+    /// `doc.rust-lang.org`). An empty list allows every host.
+    pub fn with_allowed_domains(mut self, domains: Vec<String>) -> Self {
+        self.allowed_domains = domains.into_iter().map(|d| d.trim_start_matches('.').to_lowercase()).collect();
+        self
+    }
+
+    /// Never follows links whose path starts with one of these prefixes.
+    pub fn with_denied_paths(mut self, prefixes: Vec<String>) -> Self {
+        self.denied_paths = prefixes;
+        self
+    }
+
+    /// Only follows links starting with one of these URL prefixes (scheme, host and path,
+    /// e.g. `https://docs.rs/tokio/`), to stay within one part of a site. Scheme and host
+    /// are compared case-insensitively. An empty list allows every link.
+    pub fn with_path_prefixes(mut self, prefixes: Vec<String>) -> Self {
+        self.path_prefixes = prefixes.iter().map(|p| datascraper::normalize_link(p).unwrap_or_else(|| p.clone())).collect();
+        self
+    }
+
+    /// Waits at least `delay` between two requests to the same host.
+    pub fn with_politeness_delay(mut self, delay: Duration) -> Self {
+        self.politeness_delay = delay;
+        self
+    }
+
+    /// Caps the total request rate over all hosts, see [`RateLimiter`]. Unlike the
+    /// politeness delay this also holds back a crawl spread over many hosts.
+    pub fn with_max_requests_per_second(mut self, requests_per_second: Option<f64>) -> Self {
+        self.rate_limiter = requests_per_second.map(|rate| Arc::new(RateLimiter::new(rate)));
+        self
+    }
+
+    /// Crawls towards a topic, see [`TopicFocus`].
+    pub fn with_focus(mut self, focus: Option<TopicFocus>) -> Self {
+        self.focus = focus.filter(|focus| !focus.keywords.is_empty());
+        self
+    }
+
+    /// Whether a discovered link passes the allowed domain, denied path and path prefix lists.
+    fn in_scope(&self, link: &str) -> bool {
+        if !self.path_prefixes.is_empty() && !self.path_prefixes.iter().any(|prefix| link.starts_with(prefix.as_str())) {
+            return false;
+        }
+        if self.allowed_domains.is_empty() && self.denied_paths.is_empty() {
+            return true;
         }
+        let Ok(url) = Url::parse(link) else { return false };
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        let allowed = self.allowed_domains.is_empty()
+            || self.allowed_domains.iter().any(|d| host == *d || host.ends_with(&format!(".{}", d)));
+        allowed && !self.denied_paths.iter().any(|prefix| url.path().starts_with(prefix.as_str()))
+    }
+
+    /// Stops fetching from a site once this many of its pages were crawled, so one large
+    /// site can't use up the whole page budget. `www.` and the bare host count as one site,
+    /// see [`datascraper::site_host`].
+    pub fn with_max_pages_per_domain(mut self, max: Option<usize>) -> Self {
+        self.max_pages_per_domain = max;
+        self
+    }
+
+    /// Replaces the default crawler trap thresholds.
+    pub fn with_trap_detector(mut self, traps: TrapDetector) -> Self {
+        self.traps = Arc::new(Mutex::new(traps));
+        self
+    }
+
+    /// Replaces the scoring function that orders the frontier (see [`frontier::url_priority`]).
+    /// Only affects links discovered from now on; seeds keep their original priority.
+    pub fn with_priority_fn(mut self, priority_fn: PriorityFn) -> Self {
+        self.priority_fn = priority_fn;
+        self
+    }
+
+    /// Caps the number of URLs waiting in the frontier. New links are dropped once it's full.
+    pub fn with_max_queue_size(mut self, max_queue_size: usize) -> Self {
+        self.max_queue_size = max_queue_size;
+        self
+    }
+
+    /// Uses a custom-configured scraper (proxy, headers, ...) for all fetches.
+    pub fn with_scraper(mut self, scraper: Scraper) -> Self {
+        self.scraper = scraper;
+        self
     }
     
-    pub async fn crawl(&mut self, limit: usize, concurrency: usize) -> Result<Vec<ScrapeResult>, Box<dyn std::error::Error>> {
+    /// Crawls up to `limit` pages and returns them together with counters about the run.
+    ///
+    /// A Ctrl-C during the crawl stops it gracefully, returning the pages gathered so far;
+    /// a second one cancels it, failing with [`Interrupted`] without waiting for in-flight
+    /// requests.
+    pub async fn crawl(&mut self, limit: usize, concurrency: usize) -> Result<(Vec<ScrapeResult>, Arc<CrawlStats>), Box<dyn std::error::Error>> {
+        let start = Instant::now();
+        let mut out_of_time = false;
         let mut final_results = Vec::with_capacity(limit);
-        let mut join_set = JoinSet::new();
+        let stats = Arc::new(CrawlStats::default());
+        let progress = crate::progress::bar(limit as u64, "pages", self.show_progress);
+        // Pages fetched per site, keyed by `site_host` so `www.a.test` and `a.test` share a cap
+        let mut pages_per_site: HashMap<String, usize> = HashMap::new();
+        // URLs skipped for the blocklist or the per-site cap, so links seen again count once
+        let mut skipped: HashSet<String> = HashSet::new();
+        // Earliest time the next request to each host may start, for the politeness delay
+        let mut next_fetch_at: HashMap<String, Instant> = HashMap::new();
+        // Hosts that answered 429, left alone until the time they asked for
+        let mut blocked_until: HashMap<String, Instant> = HashMap::new();
+        // URLs of blocked hosts, waiting for the block to end, and how often each was retried
+        let mut deferred: Vec<(Instant, String, usize)> = Vec::new();
+        let mut rate_limit_retries: HashMap<String, usize> = HashMap::new();
 
-        println!("Starting crawl with concurrency: {}", concurrency);
+        info!(concurrency, limit, "starting crawl");
+
+        // This loop is the only code touching the frontier during a crawl, so it takes the
+        // locks once and owns the maps until the end instead of locking per URL. Fetch tasks
+        // share nothing with it: each sends its result back over the channel.
+        let mut visited = self.visited.clone().lock_owned().await;
+        let mut queue = self.queue.clone().lock_owned().await;
+        let mut traps = self.traps.clone().lock_owned().await;
+        let (results_tx, mut results_rx) = mpsc::channel(concurrency.max(1));
+        let mut in_flight = 0;
+        let mut dump_error = None;
+        let mut cancelled = false;
+
+        // Ctrl-C stops spawning new fetches; in-flight ones are drained and
+        // everything gathered so far is returned so a partial index can be built.
+        // A second Ctrl-C cancels the crawl right away.
+        self.shutdown.store(false, Ordering::SeqCst);
+        let interrupts = InterruptGuard::install(self.shutdown.clone());
 
         loop {
-            while join_set.len() < concurrency {
-                if self.visited.lock().await.len() >= limit { break; }
-
-                let mut queue_guard = self.queue.lock().await;
-                let url_str = match queue_guard.pop_front() {
-                    Some(u) => u,
-                    None => break,
-                };
-                drop(queue_guard);
-
-                let mut visited_guard = self.visited.lock().await;
-                if visited_guard.contains(&url_str) { continue; }
-                
-                println!("Crawling: {}", url_str);
-                visited_guard.insert(url_str.clone());
-                drop(visited_guard);
+            let now = Instant::now();
+            for (_, url, depth) in deferred.extract_if(.., |(at, _, _)| *at <= now) {
+                let priority = (self.priority_fn)(&url, depth);
+                queue.push(url, depth, priority);
+            }
+
+            while in_flight < concurrency {
+                if self.shutdown.load(Ordering::SeqCst) || dump_error.is_some() { break; }
+                if let Some(max) = self.max_duration
+                    && start.elapsed() >= max
+                {
+                    if !out_of_time {
+                        warn!("Time budget of {:?} reached. Finishing in-flight requests...", max);
+                        out_of_time = true;
+                    }
+                    break;
+                }
+                if visited.len() >= limit { break; }
+
+                let Some((url_str, depth)) = queue.pop() else { break };
+
+                // Keyed like the searcher deduplicates results, so `/a` and `/a/` are one page.
+                // Checked first so a link seen again isn't counted as another skip.
+                let key = datascraper::url_key(&url_str);
+                if visited.contains(&key) || skipped.contains(&key) { continue; }
+                if self.blocklist.blocks_url(&url_str) {
+                    warn!(url = %url_str, "blocked domain, not fetching");
+                    stats.record_skip(SkipReason::Blocked);
+                    skipped.insert(key);
+                    continue;
+                }
+                let host = Url::parse(&url_str).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_default();
+                let site = datascraper::site_host(&url_str).unwrap_or_default();
+                if let Some(max) = self.max_pages_per_domain
+                    && pages_per_site.get(&site).is_some_and(|&n| n >= max)
+                {
+                    stats.record_skip(SkipReason::DomainLimit);
+                    skipped.insert(key);
+                    continue;
+                }
+
+                if let Some(&until) = blocked_until.get(&host)
+                    && until > Instant::now()
+                {
+                    deferred.push((until, url_str, depth));
+                    continue;
+                }
+                *pages_per_site.entry(site).or_insert(0) += 1;
+
+                let now = Instant::now();
+                let start_at = next_fetch_at.get(&host).copied().filter(|&t| t > now).unwrap_or(now);
+                if !self.politeness_delay.is_zero() {
+                    next_fetch_at.insert(host, start_at + self.politeness_delay);
+                }
+
+                visited.insert(key);
 
                 let scraper = self.scraper.clone();
+                let stats = stats.clone();
                 let u = url_str.clone();
+                let keep_partial = self.keep_partial;
+                let language_filter = self.language_filter.clone();
+                let blocklist = self.blocklist.clone();
+                let rate_limiter = self.rate_limiter.clone();
+                let results_tx = results_tx.clone();
 
-                join_set.spawn(async move {
-                    let fut = scraper.scrape(&u);
-                    match tokio::time::timeout(Duration::from_secs(15), fut).await {
-                        Ok(res) => (u, res),
-                        Err(_) => (u, Err("Timeout".into())),
+                let span = info_span!("fetch", url = %u, depth);
+                in_flight += 1;
+                tokio::spawn(async move {
+                    tokio::time::sleep_until(start_at.into()).await;
+                    if let Some(limiter) = &rate_limiter {
+                        limiter.acquire().await;
                     }
-                });
+                    debug!("crawling");
+                    // The scraper enforces its own request timeout, see `ScraperBuilder::request_timeout`.
+                    // Scraping runs in its own task so a panic still reports back and the loop
+                    // doesn't wait forever for this result.
+                    let url = u.clone();
+                    let res = match tokio::spawn(async move { scraper.scrape(&url).await }).await {
+                        Ok(res) => res.map_err(|e| FetchFailure::new(&*e)),
+                        Err(e) => Err(FetchFailure {
+                            reason: SkipReason::FetchError,
+                            message: format!("scrape task failed: {}", e),
+                            retry_after: None,
+                        }),
+                    };
+                    let res = res.and_then(|page| match blocklist.blocked_term(&page) {
+                        Some(term) => Err(FetchFailure {
+                            reason: SkipReason::Blocked,
+                            message: format!("contains blocked term '{}'", term),
+                            retry_after: None,
+                        }),
+                        None => Ok(page),
+                    });
+                    match &res {
+                        Ok(page) => {
+                            stats.record_download(page.bytes_downloaded);
+                            if page.is_partial && !keep_partial {
+                                stats.record_skip(SkipReason::Paywall);
+                            } else if language_filter.as_ref().is_some_and(|filter| !filter.allows(&page.language)) {
+                                stats.record_skip(SkipReason::Language);
+                            } else {
+                                stats.record_page(page);
+                            }
+                        }
+                        // Rate-limited URLs are retried; the loop records them if it gives up
+                        Err(failure) if failure.retry_after.is_some() => {}
+                        Err(failure) => stats.record_skip(failure.reason),
+                    }
+                    // The receiver lives until every spawned task has reported back
+                    let _ = results_tx.send((u, depth, res)).await;
+                }.instrument(span));
             }
 
-            if join_set.is_empty() { break; }
-
-            if let Some(res) = join_set.join_next().await {
-                if let Ok((url, result_enum)) = res {
-                    match result_enum {
-                        Ok(scrape_result) => {
-                            if !scrape_result.is_partial {
-                                let visited_cnt = self.visited.lock().await.len();
-                                if visited_cnt < limit {
-                                    let mut q = self.queue.lock().await;
-                                    for link in &scrape_result.links {
-                                        q.push_back(link.clone());
+            let stopping = self.shutdown.load(Ordering::SeqCst) || out_of_time || dump_error.is_some();
+            let next_retry = deferred.iter().map(|&(at, _, _)| at).min().filter(|_| !stopping);
+            if in_flight == 0 && next_retry.is_none() { break; }
+
+            let received = tokio::select! {
+                received = results_rx.recv() => received,
+                _ = tokio::time::sleep_until(next_retry.unwrap_or(now).into()), if next_retry.is_some() => continue,
+                _ = interrupts.cancelled.notified() => {
+                    cancelled = true;
+                    break;
+                }
+            };
+            if let Some((url, depth, result_enum)) = received {
+                in_flight -= 1;
+                if let Err(failure) = &result_enum
+                    && let Some(wait) = failure.retry_after
+                {
+                    let retries = rate_limit_retries.entry(url.clone()).or_insert(0);
+                    let host = Url::parse(&url).ok().and_then(|u| u.host_str().map(str::to_string)).unwrap_or_default();
+                    if *retries < MAX_RATE_LIMIT_RETRIES {
+                        *retries += 1;
+                        let until = Instant::now() + wait.min(MAX_RETRY_AFTER);
+                        let until = blocked_until.get(&host).map_or(until, |&t| t.max(until));
+                        warn!(url = %url, wait = ?wait, "rate limited, retrying later");
+                        blocked_until.insert(host.clone(), until);
+                        // Not fetched after all: free its slot under the page and per-host limits
+                        visited.remove(&datascraper::url_key(&url));
+                        if let Some(n) = datascraper::site_host(&url).and_then(|site| pages_per_site.get_mut(&site)) {
+                            *n = n.saturating_sub(1);
+                        }
+                        deferred.push((until, url, depth));
+                        continue;
+                    }
+                    stats.record_skip(failure.reason);
+                }
+                progress.inc(1);
+                match result_enum {
+                    Ok(scrape_result) => {
+                        if scrape_result.is_partial && !self.keep_partial {
+                            debug!(url = %url, "skipping paywalled page");
+                        } else {
+                            let boost = match &self.focus {
+                                Some(focus) => {
+                                    let score = focus::topic_score(&scrape_result, &focus.keywords);
+                                    let boost = focus.link_boost(score, depth);
+                                    if boost.is_none() {
+                                        debug!(url = %url, score, "off-topic, not following its links");
+                                    }
+                                    boost
+                                }
+                                None => Some(0),
+                            };
+                            if !scrape_result.no_follow
+                                && let Some(boost) = boost
+                            {
+                                self.enqueue_links(&mut queue, &visited, &mut traps, &scrape_result.links, depth + 1, limit, boost);
+                            }
+                            info!(
+                                url = %url,
+                                words = scrape_result.body_text.split_whitespace().count(),
+                                links = scrape_result.links.len(),
+                                lang = %scrape_result.language,
+                                "fetched"
+                            );
+                            if let Some(filter) = &self.language_filter
+                                && !filter.allows(&scrape_result.language)
+                            {
+                                debug!(url = %url, lang = %scrape_result.language, "not keeping page in this language");
+                                continue;
+                            }
+                            match &self.dump {
+                                Some(dump) => {
+                                    if let Err(e) = dump.write(&scrape_result) {
+                                        error!("Failed to write {} to the page dump: {}. Stopping the crawl.", url, e);
+                                        dump_error = Some(e);
                                     }
                                 }
-                                println!("  > Success: {} words, {} links found. [Lang: {}]", 
-                                    scrape_result.body_text.split_whitespace().count(), 
-                                    scrape_result.links.len(),
-                                    scrape_result.language
-                                );
-                                final_results.push(scrape_result);
+                                None => final_results.push(scrape_result),
                             }
                         }
-                        Err(e) => eprintln!("  > [SKIP] {}: {}", url, e),
+                    }
+                    Err(failure) => {
+                        warn!(url = %url, reason = failure.reason.label(), error = %failure.message, "skipping page")
                     }
                 }
             }
         }
 
-        Ok(final_results)
+        progress.finish_and_clear();
+        if let Some(dump) = &self.dump
+            && let Err(e) = dump.flush()
+        {
+            dump_error.get_or_insert(e);
+        }
+        if let Some(e) = dump_error {
+            return Err(format!("writing the page dump failed: {}", e).into());
+        }
+        if cancelled {
+            warn!("Crawl cancelled with {} requests in flight.", in_flight);
+            return Err(Interrupted.into());
+        }
+
+        if self.shutdown.load(Ordering::SeqCst) {
+            warn!("Crawl interrupted. Gathered {} pages before the interruption.", final_results.len());
+        }
+        info!("Crawl summary: {}", stats.summary());
+
+        Ok((final_results, stats))
+    }
+
+    /// Pushes newly discovered links, stopping once the queue is full or already holds
+    /// enough URLs to reach the page limit. `boost` is added to each link's priority.
+    #[allow(clippy::too_many_arguments)]
+    fn enqueue_links(
+        &self,
+        q: &mut Frontier,
+        visited: &HashSet<String>,
+        traps: &mut TrapDetector,
+        links: &[String],
+        depth: usize,
+        limit: usize,
+        boost: i64,
+    ) {
+        for link in links {
+            if q.len() >= self.max_queue_size || visited.len() + q.len() >= limit {
+                break;
+            }
+            if visited.contains(&datascraper::url_key(link)) || !self.in_scope(link) { continue; }
+            if self.blocklist.blocks_url(link) {
+                info!(url = %link, "blocked domain, not following");
+                continue;
+            }
+            if !traps.allow(link) { continue; }
+            q.push(link.clone(), depth, (self.priority_fn)(link, depth).saturating_add(boost));
+        }
+    }
+}
+
+/// Why a fetch task came back without a page.
+struct FetchFailure {
+    reason: SkipReason,
+    message: String,
+    /// Set when the host answered 429: how long to leave it alone before retrying.
+    retry_after: Option<Duration>,
+}
+
+impl FetchFailure {
+    fn new(error: &(dyn std::error::Error + Send + Sync + 'static)) -> Self {
+        let retry_after = match error.downcast_ref::<ScrapeError>() {
+            Some(ScrapeError::RateLimited(wait)) => Some(wait.unwrap_or(DEFAULT_RETRY_AFTER)),
+            _ => None,
+        };
+        Self { reason: skip_reason(error), message: error.to_string(), retry_after }
+    }
+}
+
+/// Buckets a failed fetch for [`CrawlStats`].
+fn skip_reason(error: &(dyn std::error::Error + Send + Sync + 'static)) -> SkipReason {
+    if let Some(error) = error.downcast_ref::<ScrapeError>() {
+        return match error {
+            ScrapeError::Status(status) if status.is_client_error() => SkipReason::ClientError,
+            ScrapeError::Status(status) if status.is_server_error() => SkipReason::ServerError,
+            ScrapeError::Status(_) => SkipReason::FetchError,
+            ScrapeError::RateLimited(_) => SkipReason::ClientError,
+            ScrapeError::UnsupportedContentType(_) => SkipReason::NonHtml,
+            ScrapeError::Timeout(_) => SkipReason::Timeout,
+            ScrapeError::Malformed(_) => SkipReason::Malformed,
+        };
     }
-}
\ No newline at end of file
+    match error.downcast_ref::<reqwest::Error>() {
+        Some(error) if error.is_timeout() => SkipReason::Timeout,
+        _ => SkipReason::FetchError,
+    }
+}
+
+/// Returned by [`Crawler::crawl`] when a second Ctrl-C cancels the crawl.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupted;
+
+impl std::fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Crawl cancelled by a second interrupt")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+/// Crawls currently handling Ctrl-C, see [`handles_interrupts`].
+static RUNNING_CRAWLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether a crawl is running and handling Ctrl-C itself.
+///
+/// Once `tokio::signal::ctrl_c` has been awaited, tokio's handler stays installed for the
+/// rest of the process and the default "Ctrl-C quits" is gone. A program that wants it back
+/// between crawls, e.g. while the pages are indexed, listens for Ctrl-C itself and leaves the
+/// ones arriving while this is true to the crawl.
+pub fn handles_interrupts() -> bool {
+    RUNNING_CRAWLS.load(Ordering::SeqCst) > 0
+}
+
+/// Routes Ctrl-C to a crawl's shutdown flag while the crawl runs: the first sets it, the
+/// second notifies `cancelled`. The listener runs on the crawl's runtime and stops with it.
+struct InterruptGuard {
+    listener: tokio::task::JoinHandle<()>,
+    cancelled: Arc<tokio::sync::Notify>,
+}
+
+impl InterruptGuard {
+    fn install(shutdown: Arc<AtomicBool>) -> Self {
+        let cancelled = Arc::new(tokio::sync::Notify::new());
+        let notify = cancelled.clone();
+        RUNNING_CRAWLS.fetch_add(1, Ordering::SeqCst);
+        let listener = tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if shutdown.swap(true, Ordering::SeqCst) {
+                    // Keeps the notification until the crawl loop waits for it
+                    notify.notify_one();
+                    return;
+                }
+                warn!("Interrupt received. Finishing in-flight requests (Ctrl-C again to cancel)...");
+            }
+        });
+        Self { listener, cancelled }
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        self.listener.abort();
+        RUNNING_CRAWLS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
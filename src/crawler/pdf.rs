@@ -0,0 +1,17 @@
+// PDF text extraction, compiled only with the `pdf` feature.
+
+use pdf_extract::{decode_text_string, Document};
+
+/// Extracts `(title, text)` from a PDF. The title comes from the document's Info dictionary.
+pub fn extract(bytes: &[u8]) -> Result<(Option<String>, String), Box<dyn std::error::Error + Send + Sync>> {
+    let text = pdf_extract::extract_text_from_mem(bytes)?;
+    Ok((title(bytes), text))
+}
+
+fn title(bytes: &[u8]) -> Option<String> {
+    let doc = Document::load_mem(bytes).ok()?;
+    let info = doc.trailer.get_deref(b"Info", &doc).ok()?.as_dict().ok()?;
+    let title = decode_text_string(info.get(b"Title").ok()?).ok()?;
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A token bucket shared by all fetch tasks, capping the total request rate of a crawl
+/// regardless of how many fetches are in flight or how many hosts are involved.
+///
+/// The bucket holds up to `burst` tokens and refills at `rate` tokens per second. Every
+/// request takes one; when none is left the caller reserves the next one and sleeps
+/// until it's due, so waiting requests are let through in arrival order at a steady pace.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests are waiting for tokens that haven't been refilled yet.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// At most `requests_per_second`, spaced evenly (a burst of 1). Panics unless the
+    /// rate is positive and finite, see [`CrawlConfig::validate`](super::config::CrawlConfig::validate).
+    pub fn new(requests_per_second: f64) -> Self {
+        assert!(requests_per_second.is_finite() && requests_per_second > 0.0, "rate must be positive");
+        Self {
+            rate: requests_per_second,
+            burst: 1.0,
+            state: Mutex::new(Bucket { tokens: 1.0, refilled_at: Instant::now() }),
+        }
+    }
+
+    /// Lets up to `burst` requests through at once after an idle period. The long-term
+    /// rate stays the same.
+    pub fn with_burst(mut self, burst: usize) -> Self {
+        self.burst = burst.max(1) as f64;
+        self.state.get_mut().expect("limiter is not shared yet").tokens = self.burst;
+        self
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.rate
+    }
+
+    /// Waits until the caller may send one request.
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token and returns how long to wait until it's actually available.
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * self.rate;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst) - 1.0;
+        bucket.refilled_at = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+}
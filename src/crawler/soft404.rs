@@ -0,0 +1,68 @@
+use url::Url;
+
+/// Heuristics for "soft 404s": error pages served with HTTP 200.
+///
+/// A page is flagged when its title matches a title pattern, when a short page's body
+/// matches a body pattern, or when a deep URL redirected to the site's homepage and the
+/// result is short. Body patterns only count on short pages since long articles often
+/// mention "not found" in passing. Patterns are matched case-insensitively as substrings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Soft404Detector {
+    pub title_patterns: Vec<String>,
+    pub body_patterns: Vec<String>,
+    /// Pages with at most this many words count as short.
+    pub short_body_words: usize,
+}
+
+impl Default for Soft404Detector {
+    fn default() -> Self {
+        let patterns = |list: &[&str]| list.iter().map(|p| p.to_string()).collect();
+        Self {
+            title_patterns: patterns(&[
+                "404", "not found", "page not found", "doesn't exist", "does not exist",
+                "no longer available", "page unavailable",
+            ]),
+            body_patterns: patterns(&[
+                "404", "page not found", "page you requested", "page you are looking for",
+                "page doesn't exist", "page does not exist", "could not be found",
+                "no longer available", "has been removed",
+            ]),
+            short_body_words: 150,
+        }
+    }
+}
+
+impl Soft404Detector {
+    /// A detector that never flags anything.
+    pub fn disabled() -> Self {
+        Self { title_patterns: Vec::new(), body_patterns: Vec::new(), short_body_words: 0 }
+    }
+
+    /// `requested` is the URL asked for, `landed` the one after redirects.
+    pub fn is_soft_404(&self, requested: &Url, landed: &Url, title: Option<&str>, body: &str) -> bool {
+        if let Some(title) = title
+            && contains_any(title, &self.title_patterns)
+        {
+            return true;
+        }
+
+        let short = body.split_whitespace().take(self.short_body_words + 1).count() <= self.short_body_words;
+        if !short {
+            return false;
+        }
+        if contains_any(body, &self.body_patterns) {
+            return true;
+        }
+
+        let redirected_home = requested.path() != "/" && landed.path() == "/" && requested.host() == landed.host();
+        redirected_home && self.short_body_words > 0
+    }
+}
+
+fn contains_any(text: &str, patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return false;
+    }
+    let text = text.to_lowercase();
+    patterns.iter().any(|p| text.contains(&p.to_lowercase()))
+}
@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use super::datascraper::ScrapeResult;
+
+/// Rough share of the body text size that ends up on disk (compressed postings + positions).
+const INDEX_BYTES_PER_BODY_BYTE: f64 = 0.35;
+
+/// Why a page didn't make it into the crawl results (or, for the last three, the index).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Not fetched: the per-domain page cap was reached.
+    DomainLimit,
+    /// Request didn't complete in time.
+    Timeout,
+    /// HTTP 4xx response.
+    ClientError,
+    /// HTTP 5xx response.
+    ServerError,
+    /// Any other network or protocol failure.
+    FetchError,
+    /// Fetched, but its content couldn't be extracted.
+    Malformed,
+    /// Content type other than HTML (or PDF, when enabled).
+    NonHtml,
+    /// Only the teaser of a paywalled page was available.
+    Paywall,
+    /// In a language the crawl wasn't asked to keep; its links were still followed.
+    Language,
+    /// On a blocked domain, or containing a blocked term, see [`Blocklist`](super::blocklist::Blocklist).
+    Blocked,
+    /// The page asked not to be indexed (robots meta tag or `X-Robots-Tag`).
+    Robots,
+    /// The body has fewer words than the indexer's minimum.
+    ThinContent,
+    /// Looks like an error page served with HTTP 200.
+    Soft404,
+}
+
+impl SkipReason {
+    pub const ALL: [SkipReason; 13] = [
+        SkipReason::DomainLimit,
+        SkipReason::Timeout,
+        SkipReason::ClientError,
+        SkipReason::ServerError,
+        SkipReason::FetchError,
+        SkipReason::Malformed,
+        SkipReason::NonHtml,
+        SkipReason::Paywall,
+        SkipReason::Language,
+        SkipReason::Blocked,
+        SkipReason::Robots,
+        SkipReason::ThinContent,
+        SkipReason::Soft404,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::DomainLimit => "domain limit",
+            SkipReason::Timeout => "timeout",
+            SkipReason::ClientError => "4xx",
+            SkipReason::ServerError => "5xx",
+            SkipReason::FetchError => "fetch error",
+            SkipReason::Malformed => "malformed",
+            SkipReason::NonHtml => "non-HTML",
+            SkipReason::Paywall => "paywall",
+            SkipReason::Language => "language",
+            SkipReason::Blocked => "blocked",
+            SkipReason::Robots => "robots",
+            SkipReason::ThinContent => "thin content",
+            SkipReason::Soft404 => "soft 404",
+        }
+    }
+}
+
+/// Aggregate counters for one crawl. Shared as an `Arc<CrawlStats>` and updated from
+/// the fetch tasks, so every counter is atomic.
+#[derive(Debug, Default)]
+pub struct CrawlStats {
+    pages_fetched: AtomicUsize,
+    skipped: [AtomicUsize; SkipReason::ALL.len()],
+    links_discovered: AtomicUsize,
+    bytes_downloaded: AtomicU64,
+    total_body_bytes: AtomicU64,
+    total_stored_bytes: AtomicU64,
+    languages: Mutex<BTreeMap<String, usize>>,
+}
+
+impl CrawlStats {
+    pub fn record_page(&self, page: &ScrapeResult) {
+        self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+        self.links_discovered.fetch_add(page.links.len(), Ordering::Relaxed);
+        self.total_body_bytes.fetch_add(page.body_text.len() as u64, Ordering::Relaxed);
+        let stored = page.url.len() + page.title.as_deref().map_or(0, str::len);
+        self.total_stored_bytes.fetch_add(stored as u64, Ordering::Relaxed);
+        let mut languages = self.languages.lock().unwrap_or_else(|e| e.into_inner());
+        *languages.entry(page.language.clone()).or_insert(0) += 1;
+    }
+
+    pub fn record_skip(&self, reason: SkipReason) {
+        self.skipped[reason as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_download(&self, bytes: u64) {
+        self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Pages fetched and parsed successfully.
+    pub fn pages_fetched(&self) -> usize {
+        self.pages_fetched.load(Ordering::Relaxed)
+    }
+
+    pub fn skipped(&self, reason: SkipReason) -> usize {
+        self.skipped[reason as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn pages_skipped(&self) -> usize {
+        SkipReason::ALL.iter().map(|&r| self.skipped(r)).sum()
+    }
+
+    /// Links found on fetched pages, before deduplication against the frontier.
+    pub fn links_discovered(&self) -> usize {
+        self.links_discovered.load(Ordering::Relaxed)
+    }
+
+    /// Response body bytes received (after decompression).
+    pub fn bytes_downloaded(&self) -> u64 {
+        self.bytes_downloaded.load(Ordering::Relaxed)
+    }
+
+    /// Fetched pages per detected language code, most common first.
+    pub fn languages(&self) -> Vec<(String, usize)> {
+        let languages = self.languages.lock().unwrap_or_else(|e| e.into_inner());
+        let mut counts: Vec<(String, usize)> = languages.iter().map(|(l, n)| (l.clone(), *n)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    pub fn average_body_bytes(&self) -> u64 {
+        self.total_body_bytes.load(Ordering::Relaxed).checked_div(self.pages_fetched() as u64).unwrap_or(0)
+    }
+
+    /// Ballpark size of an index built from the fetched pages. Only meant for comparing
+    /// crawl settings, not for capacity planning.
+    pub fn estimated_index_bytes(&self) -> u64 {
+        let body = self.total_body_bytes.load(Ordering::Relaxed) as f64;
+        (body * INDEX_BYTES_PER_BODY_BYTE) as u64 + self.total_stored_bytes.load(Ordering::Relaxed)
+    }
+
+    /// One-line overview, e.g. `120 fetched, 8 skipped (timeout: 3, 4xx: 5), 4.2 MB downloaded`.
+    pub fn summary(&self) -> String {
+        let reasons: Vec<String> = SkipReason::ALL
+            .iter()
+            .filter(|&&r| self.skipped(r) > 0)
+            .map(|&r| format!("{}: {}", r.label(), self.skipped(r)))
+            .collect();
+        let reasons = if reasons.is_empty() { String::new() } else { format!(" ({})", reasons.join(", ")) };
+        format!(
+            "{} fetched, {} skipped{}, {:.1} MB downloaded",
+            self.pages_fetched(),
+            self.pages_skipped(),
+            reasons,
+            self.bytes_downloaded() as f64 / 1_000_000.0
+        )
+    }
+}
+
+impl fmt::Display for CrawlStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pages fetched:      {}", self.pages_fetched())?;
+        writeln!(f, "Pages skipped:      {}", self.pages_skipped())?;
+        for reason in SkipReason::ALL.iter().filter(|&&r| self.skipped(r) > 0) {
+            writeln!(f, "  {:<18}{}", format!("{}:", reason.label()), self.skipped(*reason))?;
+        }
+        writeln!(f, "Links discovered:   {}", self.links_discovered())?;
+        writeln!(f, "Downloaded:         {:.1} MB", self.bytes_downloaded() as f64 / 1_000_000.0)?;
+        let languages: Vec<String> = self.languages().iter().map(|(lang, n)| format!("{}: {}", lang, n)).collect();
+        writeln!(f, "Languages:          {}", if languages.is_empty() { "-".to_string() } else { languages.join(", ") })?;
+        writeln!(f, "Average body size:  {} bytes", self.average_body_bytes())?;
+        write!(f, "Estimated index:    {:.1} MB", self.estimated_index_bytes() as f64 / 1_000_000.0)
+    }
+}
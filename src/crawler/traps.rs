@@ -0,0 +1,84 @@
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+pub const DEFAULT_MAX_QUERY_VARIANTS: usize = 50;
+pub const DEFAULT_MAX_PATH_SEGMENTS: usize = 12;
+/// A segment showing up this many times (`/a/b/a/b/a/`) means the site is looping.
+const MAX_SEGMENT_REPEATS: usize = 3;
+
+/// Spots URL spaces that never end, like calendars or faceted navigation,
+/// so the crawler doesn't spend its whole budget inside one of them.
+#[derive(Debug)]
+pub struct TrapDetector {
+    max_query_variants: usize,
+    max_path_segments: usize,
+    // "host/path" -> distinct query strings seen for it
+    query_variants: HashMap<String, HashSet<String>>,
+    suppressed: HashSet<String>,
+}
+
+impl Default for TrapDetector {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_QUERY_VARIANTS, DEFAULT_MAX_PATH_SEGMENTS)
+    }
+}
+
+impl TrapDetector {
+    pub fn new(max_query_variants: usize, max_path_segments: usize) -> Self {
+        Self {
+            max_query_variants,
+            max_path_segments,
+            query_variants: HashMap::new(),
+            suppressed: HashSet::new(),
+        }
+    }
+
+    /// Returns false if `url` looks like part of a crawler trap. Each suppressed
+    /// pattern is logged once.
+    pub fn allow(&mut self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else {
+            return true;
+        };
+        let host = parsed.host_str().unwrap_or_default();
+        let pattern = format!("{}{}", host, parsed.path());
+
+        if self.suppressed.contains(&pattern) {
+            return false;
+        }
+
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|s| s.filter(|seg| !seg.is_empty()).collect())
+            .unwrap_or_default();
+
+        if segments.len() > self.max_path_segments || has_repeated_segment(&segments) {
+            return self.suppress(pattern);
+        }
+
+        if let Some(query) = parsed.query() {
+            let variants = self.query_variants.entry(pattern.clone()).or_default();
+            if variants.len() >= self.max_query_variants && !variants.contains(query) {
+                self.query_variants.remove(&pattern);
+                return self.suppress(pattern);
+            }
+            variants.insert(query.to_string());
+        }
+
+        true
+    }
+
+    fn suppress(&mut self, pattern: String) -> bool {
+        tracing::info!(%pattern, "suppressing crawler trap");
+        self.suppressed.insert(pattern);
+        false
+    }
+}
+
+fn has_repeated_segment(segments: &[&str]) -> bool {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    segments.iter().any(|seg| {
+        let count = counts.entry(seg).or_insert(0);
+        *count += 1;
+        *count >= MAX_SEGMENT_REPEATS
+    })
+}
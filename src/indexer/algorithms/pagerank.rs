@@ -1,17 +1,109 @@
 use std::collections::{HashMap, HashSet};
 use rayon::prelude::*; 
+use serde::{Deserialize, Serialize};
+
+use crate::crawler::datascraper::is_internal_link;
 
 pub type LinkGraph = HashMap<String, HashSet<String>>;
 pub type PageRanks = HashMap<String, f64>;
 
-const DAMPING_FACTOR: f64 = 0.85; 
-const MAX_ITERATIONS: usize = 100; 
-const CONVERGENCE_THRESHOLD: f64 = 0.0001;
+pub const DEFAULT_DAMPING_FACTOR: f64 = 0.85; 
+pub const DEFAULT_MAX_ITERATIONS: usize = 100; 
+pub const DEFAULT_CONVERGENCE_THRESHOLD: f64 = 0.0001;
+/// Same-site links count fully by default, like in the classic algorithm.
+pub const DEFAULT_INTERNAL_LINK_WEIGHT: f64 = 1.0;
+
+/// PageRank parameters. `damping` is the chance a random surfer follows a link rather
+/// than jumping to a random page; iteration stops after `max_iterations` or once the
+/// ranks change by less than `convergence_threshold` in total.
+///
+/// `internal_link_weight` (0 to 1) is how much a same-site link counts compared to one
+/// from another site. Below 1, a page passes less of its rank along its navigation
+/// links and more along its external ones, so authority reflects what other sites link
+/// to rather than how heavily a site links to itself. A page with only internal links
+/// and a weight of 0 is treated like one without links.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageRankConfig {
+    pub damping: f64,
+    pub max_iterations: usize,
+    pub convergence_threshold: f64,
+    pub internal_link_weight: f64,
+}
+
+impl Default for PageRankConfig {
+    fn default() -> Self {
+        Self {
+            damping: DEFAULT_DAMPING_FACTOR,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            convergence_threshold: DEFAULT_CONVERGENCE_THRESHOLD,
+            internal_link_weight: DEFAULT_INTERNAL_LINK_WEIGHT,
+        }
+    }
+}
+
+impl PageRankConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.damping > 0.0 && self.damping < 1.0) {
+            return Err(format!("Damping factor must be between 0 and 1 (exclusive), got {}.", self.damping));
+        }
+        if self.max_iterations == 0 {
+            return Err("PageRank needs at least 1 iteration.".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.internal_link_weight) {
+            return Err(format!("Internal link weight must be between 0 and 1, got {}.", self.internal_link_weight));
+        }
+        Ok(())
+    }
+}
 
+/// One link of a [`LinkGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkEdge<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    /// Both ends are on the same site, see [`is_internal_link`].
+    pub is_internal: bool,
+}
+
+/// Every link of `link_graph`, tagged as internal or external.
+pub fn edges(link_graph: &LinkGraph) -> impl Iterator<Item = LinkEdge<'_>> {
+    link_graph.iter().flat_map(|(from, targets)| {
+        targets.iter().map(move |to| LinkEdge { from, to, is_internal: is_internal_link(from, to) })
+    })
+}
+
+/// Keeps only edges between crawled pages (the graph's keys) and drops self-links.
+///
+/// Without pruning, every linked-but-never-crawled URL is a node with no out-links, so
+/// part of the rank mass flows to pages that aren't in the index and gets spread back
+/// uniformly as dangling mass. Pruning keeps the mass within the crawled set, which
+/// favours pages the crawl itself links to heavily.
+pub fn prune_to_crawled(link_graph: &LinkGraph) -> LinkGraph {
+    link_graph
+        .iter()
+        .map(|(url, targets)| {
+            let targets = targets
+                .iter()
+                .filter(|target| *target != url && link_graph.contains_key(*target))
+                .cloned()
+                .collect();
+            (url.clone(), targets)
+        })
+        .collect()
+}
+
+/// PageRank with the default [`PageRankConfig`].
 pub fn calculate_pagerank(link_graph: &LinkGraph) -> PageRanks {
+    calculate_pagerank_with(link_graph, &PageRankConfig::default()).0
+}
+
+/// PageRank of every page in `link_graph`, along with the number of iterations run.
+pub fn calculate_pagerank_with(link_graph: &LinkGraph, config: &PageRankConfig) -> (PageRanks, usize) {
     if link_graph.is_empty() {
-        return HashMap::new();
+        return (HashMap::new(), 0);
     }
+    let damping = config.damping;
 
     // 1. Collect all unique URLs
     let all_urls: HashSet<String> = link_graph
@@ -27,49 +119,59 @@ pub fn calculate_pagerank(link_graph: &LinkGraph) -> PageRanks {
     let mut ranks: PageRanks = all_urls.iter().map(|url| (url.clone(), initial_rank)).collect();
     let all_urls_vec: Vec<String> = all_urls.into_iter().collect();
 
-    // 2. Build Reverse Graph & Identify Dangling Nodes (pages with no outgoing links)
-    let mut incoming_links: HashMap<String, Vec<String>> = HashMap::new();
+    // 2. Build Reverse Graph & Identify Dangling Nodes (pages with no outgoing links).
+    // Each incoming link carries the share of its source's rank it passes on.
+    let mut incoming_links: HashMap<String, Vec<(String, f64)>> = HashMap::new();
     let mut dangling_nodes: Vec<String> = Vec::new();
 
     for url in &all_urls_vec {
-        if let Some(outgoing) = link_graph.get(url) {
-            if outgoing.is_empty() {
-                dangling_nodes.push(url.clone());
-            } else {
-                for target in outgoing {
-                    incoming_links.entry(target.clone()).or_default().push(url.clone());
-                }
-            }
-        } else {
+        let weighted: Vec<(&String, f64)> = link_graph
+            .get(url)
+            .into_iter()
+            .flatten()
+            .map(|target| {
+                let weight = if is_internal_link(url, target) { config.internal_link_weight } else { 1.0 };
+                (target, weight)
+            })
+            .collect();
+        let total_weight: f64 = weighted.iter().map(|(_, weight)| weight).sum();
+        if total_weight <= 0.0 {
             dangling_nodes.push(url.clone());
+            continue;
+        }
+        for (target, weight) in weighted {
+            if weight > 0.0 {
+                incoming_links.entry(target.clone()).or_default().push((url.clone(), weight / total_weight));
+            }
         }
     }
 
     // 3. Iterative Calculation
-    for i in 0..MAX_ITERATIONS {
+    let mut iterations = 0;
+    for i in 0..config.max_iterations {
+        iterations = i + 1;
         // Calculate mass from dangling nodes to redistribute
         let dangling_sum: f64 = dangling_nodes.iter()
             .map(|u| *ranks.get(u).unwrap_or(&0.0))
             .sum();
             
-        let dangling_weight = (DAMPING_FACTOR * dangling_sum) / num_pages;
-        let random_jump_rank = (1.0 - DAMPING_FACTOR) / num_pages;
+        let dangling_weight = (damping * dangling_sum) / num_pages;
+        let random_jump_rank = (1.0 - damping) / num_pages;
         let base_rank = random_jump_rank + dangling_weight;
 
         // Parallel update using Rayon
         let new_ranks: PageRanks = all_urls_vec.par_iter()
             .map(|url| {
                 let rank_from_links: f64 = if let Some(sources) = incoming_links.get(url) {
-                    sources.iter().map(|source_url| {
+                    sources.iter().map(|(source_url, share)| {
                         let source_rank = *ranks.get(source_url).unwrap_or(&0.0);
-                        let source_out_degree = link_graph.get(source_url).unwrap().len() as f64;
-                        source_rank / source_out_degree
+                        source_rank * share
                     }).sum()
                 } else {
                     0.0
                 };
 
-                let new_rank = base_rank + (DAMPING_FACTOR * rank_from_links);
+                let new_rank = base_rank + (damping * rank_from_links);
                 (url.clone(), new_rank)
             })
             .collect();
@@ -85,11 +187,11 @@ pub fn calculate_pagerank(link_graph: &LinkGraph) -> PageRanks {
 
         ranks = new_ranks;
 
-        if total_change < CONVERGENCE_THRESHOLD {
-            println!("PageRank converged after {} iterations.", i + 1);
+        if total_change < config.convergence_threshold {
+            tracing::debug!("PageRank converged after {} iterations.", i + 1);
             break;
         }
     }
 
-    ranks
+    (ranks, iterations)
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use tantivy::schema::{Field, Value};
+use tantivy::{Index, TantivyDocument};
+use url::Url;
+
+use super::algorithms::pagerank::{LinkGraph, PageRanks};
+
+/// Output format of [`export_index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line.
+    JsonLines,
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" | "json" => Ok(Self::JsonLines),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!("Unknown export format '{}'. Use 'jsonl' or 'csv'.", other)),
+        }
+    }
+}
+
+/// One exported document. Fields missing from older indexes are left empty.
+#[derive(Debug, Serialize)]
+struct ExportedDoc {
+    url: String,
+    title: String,
+    language: String,
+    pagerank: f64,
+    crawled_at: Option<i64>,
+    favicon: String,
+}
+
+/// Dumps every live document of the index at `index_path` to `output` (stdout if `None`).
+/// Returns the number of documents written.
+pub fn export_index(index_path: &str, format: ExportFormat, output: Option<&str>) -> Result<usize, Box<dyn std::error::Error>> {
+    let index = Index::open_in_dir(index_path)?;
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    let count = match format {
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            let count = for_each_doc(&index, |record| Ok(csv.serialize(record)?))?;
+            csv.flush()?;
+            count
+        }
+        ExportFormat::JsonLines => {
+            let count = for_each_doc(&index, |record| {
+                writeln!(writer, "{}", serde_json::to_string(record)?)?;
+                Ok(())
+            })?;
+            writer.flush()?;
+            count
+        }
+    };
+    Ok(count)
+}
+
+/// Reads stored documents segment by segment, skipping deleted ones.
+fn for_each_doc(
+    index: &Index,
+    mut write: impl FnMut(&ExportedDoc) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let schema = index.schema();
+    // Look fields up by name so indexes built with an older schema still export
+    let field = |name: &str| schema.get_field(name).ok();
+    let (url, title, language, pagerank, crawled_at, favicon) =
+        (field("url"), field("title"), field("language"), field("pagerank"), field("crawled_at"), field("favicon"));
+
+    let searcher = index.reader()?.searcher();
+    let mut count = 0;
+
+    for segment_reader in searcher.segment_readers() {
+        let store_reader = segment_reader.get_store_reader(64)?;
+        for doc in store_reader.iter::<TantivyDocument>(segment_reader.alive_bitset()) {
+            let doc = doc?;
+            let text = |f: Option<Field>| {
+                f.and_then(|f| doc.get_first(f))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            write(&ExportedDoc {
+                url: text(url),
+                title: text(title),
+                language: text(language),
+                pagerank: pagerank.and_then(|f| doc.get_first(f)).and_then(|v| v.as_f64()).unwrap_or(0.0),
+                crawled_at: crawled_at.and_then(|f| doc.get_first(f)).and_then(|v| v.as_i64()),
+                favicon: text(favicon),
+            })?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Writes the crawled link graph as a GraphViz `.dot` file.
+///
+/// Nodes are URLs labeled by their domain, edges follow links. When `ranks` is
+/// given, node size scales with PageRank. Render with e.g. `dot -Tsvg graph.dot`.
+pub fn export_link_graph_dot(graph: &LinkGraph, ranks: Option<&PageRanks>, path: &str) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    let nodes: HashSet<&String> = graph.keys().chain(graph.values().flatten()).collect();
+    let max_rank = ranks
+        .and_then(|r| r.values().cloned().reduce(f64::max))
+        .unwrap_or(0.0);
+
+    writeln!(out, "digraph crawl {{")?;
+    writeln!(out, "    node [shape=ellipse, fontsize=10];")?;
+
+    for url in &nodes {
+        let label = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string());
+
+        match ranks.and_then(|r| r.get(*url)) {
+            Some(rank) if max_rank > 0.0 => {
+                // Scale to a visible range: the top page is 2.5 inches wide, the rest proportionally
+                let width = 0.3 + 2.2 * (rank / max_rank);
+                writeln!(out, "    \"{}\" [label=\"{}\", width={:.2}, tooltip=\"{:.6}\"];", escape(url), escape(&label), width, rank)?;
+            }
+            _ => writeln!(out, "    \"{}\" [label=\"{}\"];", escape(url), escape(&label))?,
+        }
+    }
+
+    for (source, targets) in graph {
+        for target in targets {
+            writeln!(out, "    \"{}\" -> \"{}\";", escape(source), escape(target))?;
+        }
+    }
+
+    writeln!(out, "}}")?;
+    out.flush()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,297 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tracing::{error, info, warn};
+use url::Url;
+
+use super::{index_pages, IndexBackend, IndexerConfig};
+use crate::crawler::datascraper::ScrapeResult;
+use crate::crawler::jsonld::StructuredData;
+use crate::crawler::language::LanguageDetector;
+
+/// File extensions [`index_files`] reads; anything else matched by the pattern is skipped.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["txt", "md", "markdown"];
+
+/// Indexes the `.txt` and Markdown files matching `pattern` (see [`expand_glob`]) into
+/// `backend`, replacing its contents. Pages are identified by their `file://` URL and
+/// get a PageRank of 0 since local files have no link graph.
+pub fn index_files(pattern: &str, backend: &IndexBackend, config: &IndexerConfig) -> io::Result<tantivy::Index> {
+    let language = config.crawl.language_detector().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut pages = Vec::new();
+    for path in expand_glob(pattern)? {
+        if !has_supported_extension(&path) {
+            continue;
+        }
+        match read_document(&path, &language) {
+            Ok(page) => pages.push(page),
+            Err(e) => warn!("Skipping '{}': {}", path.display(), e),
+        }
+    }
+    info!("Read {} files matching '{}'.", pages.len(), pattern);
+
+    // The pattern stands in for the seeds in the index metadata
+    let mut crawl = config.crawl.clone();
+    crawl.seeds = vec![pattern.to_string()];
+    let config = IndexerConfig { crawl, pagerank: false, export_dot: None, ..config.clone() };
+    index_pages(pages, backend, &config).map_err(io::Error::other)
+}
+
+/// Runs [`index_files`] into the index directory at `index_path`.
+pub fn run_file_indexer(index_path: &str, pattern: &str, config: &IndexerConfig) {
+    if let Err(e) = config.validate() {
+        error!("Invalid configuration: {}", e);
+        return;
+    }
+    if let Err(e) = index_files(pattern, &IndexBackend::Disk(index_path.into()), config) {
+        error!("Indexing files failed: {}", e);
+    }
+}
+
+fn has_supported_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.iter().any(|s| ext.eq_ignore_ascii_case(s)))
+}
+
+/// A local file as a page: Markdown syntax is stripped from the body and the first
+/// heading becomes the title, falling back to the file name.
+pub fn read_document(path: &Path, language: &LanguageDetector) -> io::Result<ScrapeResult> {
+    let content = std::fs::read_to_string(path)?;
+    let absolute = std::fs::canonicalize(path)?;
+    let url = Url::from_file_path(&absolute)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path can't be turned into a file:// URL"))?;
+
+    let is_markdown = path.extension().is_some_and(|ext| !ext.eq_ignore_ascii_case("txt"));
+    let (title, body_text) = if is_markdown {
+        (first_heading(&content), strip_markdown(&content))
+    } else {
+        (None, content.split_whitespace().collect::<Vec<_>>().join(" "))
+    };
+    let title = title.or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()));
+
+    Ok(ScrapeResult {
+        url: url.to_string(),
+        title,
+        language: language.detect(&body_text),
+        body_text,
+        links: Vec::new(),
+        internal_links: 0,
+        external_links: 0,
+        anchors: Vec::new(),
+        is_partial: false,
+        word_counts: None,
+        structured: StructuredData::default(),
+        no_index: false,
+        no_follow: false,
+        likely_404: false,
+        bytes_downloaded: content.len() as u64,
+        favicon: None,
+        status: None,
+        redirected_from: None,
+        fetched_at: None,
+    })
+}
+
+/// Text of the first ATX heading (`# Title`, `## Title`, ...) outside code blocks.
+fn first_heading(markdown: &str) -> Option<String> {
+    let mut in_fence = false;
+    for line in markdown.lines().map(str::trim) {
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence && let Some(heading) = heading_text(line) {
+            let title = strip_inline(heading);
+            if !title.is_empty() {
+                return Some(title);
+            }
+        }
+    }
+    None
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches('#');
+    let level = line.len() - rest.len();
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')))
+        .then(|| rest.trim().trim_end_matches('#').trim_end())
+}
+
+/// Plain text of a Markdown document: heading, list, quote and emphasis markers, link
+/// targets, inline HTML tags and table pipes are dropped; link texts, image alt texts and
+/// code are kept. Whitespace is normalized like scraped page text.
+pub fn strip_markdown(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            text.push_str(line);
+            text.push(' ');
+            continue;
+        }
+        if is_rule_or_table_separator(line) || is_link_definition(line) {
+            continue;
+        }
+        let line = line.trim_start_matches(|c: char| c == '>' || c.is_whitespace());
+        let line = heading_text(line).unwrap_or(line);
+        text.push_str(&strip_inline(strip_list_marker(line)));
+        text.push(' ');
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `---`, `***`, `___` and table rows like `|---|:--:|`.
+fn is_rule_or_table_separator(line: &str) -> bool {
+    let marks = line.chars().filter(|c| !c.is_whitespace()).count();
+    marks >= 3 && line.chars().all(|c| matches!(c, '-' | '*' | '_' | '|' | ':' | ' ' | '='))
+}
+
+/// `[id]: https://example.com "Title"`
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[') && line.split_once("]:").is_some_and(|(label, _)| !label.contains(']'))
+}
+
+fn strip_list_marker(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix(['-', '*', '+']).filter(|rest| rest.starts_with(' ')) {
+        return rest;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    match line[digits..].strip_prefix(['.', ')']) {
+        Some(rest) if digits > 0 && rest.starts_with(' ') => rest,
+        _ => line,
+    }
+}
+
+/// Removes inline syntax from one line of Markdown.
+fn strip_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            // `[text](target)`, `![alt](src)`, `[text][ref]`: keep the text
+            '!' if chars.get(i + 1) == Some(&'[') => i += 1,
+            '[' => {
+                if let Some(close) = find(&chars, i + 1, ']') {
+                    out.extend(&chars[i + 1..close]);
+                    i = close + 1;
+                    let target_end = match chars.get(i) {
+                        Some('(') => find(&chars, i + 1, ')'),
+                        Some('[') => find(&chars, i + 1, ']'),
+                        _ => None,
+                    };
+                    if let Some(end) = target_end {
+                        i = end + 1;
+                    }
+                    continue;
+                }
+                out.push(c);
+                i += 1;
+            }
+            // Inline HTML such as `<br>` or `</span>`
+            '<' if chars.get(i + 1).is_some_and(|n| n.is_ascii_alphabetic() || *n == '/') => {
+                match find(&chars, i + 1, '>') {
+                    Some(close) => i = close + 1,
+                    None => {
+                        out.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '`' | '*' | '~' => i += 1,
+            '|' => {
+                out.push(' ');
+                i += 1;
+            }
+            // Underscores are emphasis at word boundaries, but part of identifiers like `snake_case`
+            '_' => {
+                let inside_word = i > 0
+                    && chars[i - 1].is_alphanumeric()
+                    && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric());
+                if inside_word {
+                    out.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn find(chars: &[char], from: usize, target: char) -> Option<usize> {
+    chars[from.min(chars.len())..].iter().position(|&c| c == target).map(|offset| from + offset)
+}
+
+/// Files matching `pattern`, sorted. `*` matches any characters within a path component,
+/// `?` a single character and `**` any number of directories, e.g. `docs/**/*.md`.
+/// Hidden files and directories (starting with `.`) are never matched by a wildcard.
+pub fn expand_glob(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty() && *c != ".").collect();
+    let literal = components.iter().take_while(|c| !c.contains(['*', '?'])).count();
+    let mut root = PathBuf::from(if pattern.starts_with('/') { "/" } else { "." });
+    root.extend(&components[..literal]);
+
+    if literal == components.len() {
+        return Ok(if root.is_file() { vec![root] } else { Vec::new() });
+    }
+    if !root.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("directory '{}' not found", root.display())));
+    }
+
+    let mut matches = Vec::new();
+    walk(&root, &mut Vec::new(), &components[literal..], &mut matches)?;
+    matches.sort();
+    Ok(matches)
+}
+
+fn walk(dir: &Path, relative: &mut Vec<String>, pattern: &[&str], matches: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        relative.push(name);
+        if file_type.is_dir() {
+            walk(&entry.path(), relative, pattern, matches)?;
+        } else if file_type.is_file() && matches_components(pattern, relative) {
+            matches.push(entry.path());
+        }
+        relative.pop();
+    }
+    Ok(())
+}
+
+fn matches_components(pattern: &[&str], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_components(rest, &path[skip..])),
+        Some((component, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                let (component, name): (Vec<char>, Vec<char>) = (component.chars().collect(), name.chars().collect());
+                matches_segment(&component, &name) && matches_components(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// `*` and `?` wildcards within one path component.
+fn matches_segment(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some(('*', rest)), _) => (0..=name.len()).any(|skip| matches_segment(rest, &name[skip..])),
+        (Some(('?', rest)), Some((_, name_rest))) => matches_segment(rest, name_rest),
+        (Some((p, rest)), Some((n, name_rest))) => p == n && matches_segment(rest, name_rest),
+        _ => false,
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use tantivy::{Directory, HasLen, Index, IndexWriter, TantivyDocument};
+
+use super::MIN_WRITER_MEMORY_MB;
+
+/// Size and shape of an index, as reported by `du`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexUsage {
+    /// Total size of the index files.
+    pub bytes: u64,
+    pub files: usize,
+    pub segments: usize,
+    /// Live documents.
+    pub docs: u64,
+    /// Deleted documents still taking up space until their segment is merged.
+    pub deleted_docs: u64,
+}
+
+impl IndexUsage {
+    /// Measures `index`. Only the files tantivy manages count, so e.g. `index_meta.json`
+    /// and the lock files are left out.
+    pub fn of(index: &Index) -> tantivy::Result<Self> {
+        let directory = index.directory();
+        let (mut bytes, mut files) = (0, 0);
+        for path in directory.list_managed_files() {
+            // Files listed as managed may already be gone after a merge
+            let Ok(file) = directory.open_read(&path) else { continue };
+            bytes += file.len() as u64;
+            files += 1;
+        }
+        let segments = index.searchable_segment_metas()?;
+        Ok(Self {
+            bytes,
+            files,
+            segments: segments.len(),
+            docs: segments.iter().map(|s| u64::from(s.num_docs())).sum(),
+            deleted_docs: segments.iter().map(|s| u64::from(s.num_deleted_docs())).sum(),
+        })
+    }
+}
+
+impl std::fmt::Display for IndexUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} MB in {} files, {} segment{}, {} documents ({} deleted)",
+            self.bytes as f64 / 1_000_000.0,
+            self.files,
+            self.segments,
+            if self.segments == 1 { "" } else { "s" },
+            self.docs,
+            self.deleted_docs
+        )
+    }
+}
+
+/// Merges all segments of `index` into one, dropping deleted documents for good, and
+/// removes the files no longer used. Does nothing to an index that already is a single
+/// segment without deletions. Returns the usage before and after.
+///
+/// Like any write, this waits for the index lock, so it can't run during indexing;
+/// searchers keep working and see the merged index on their next reload.
+pub fn compact(index: &Index) -> tantivy::Result<(IndexUsage, IndexUsage)> {
+    let before = IndexUsage::of(index)?;
+    let mut writer: IndexWriter<TantivyDocument> = index.writer_with_num_threads(1, MIN_WRITER_MEMORY_MB * 1_000_000)?;
+    let segment_ids = index.searchable_segment_ids()?;
+    if segment_ids.len() > 1 || before.deleted_docs > 0 {
+        writer.merge(&segment_ids).wait()?;
+    }
+    writer.garbage_collect_files().wait()?;
+    writer.wait_merging_threads()?;
+    Ok((before, IndexUsage::of(index)?))
+}
+
+/// Prints the [`IndexUsage`] of the index in `index_path`.
+pub fn run_disk_usage(index_path: &str) {
+    match Index::open_in_dir(Path::new(index_path)).and_then(|index| IndexUsage::of(&index)) {
+        Ok(usage) => println!("{}: {}", index_path, usage),
+        Err(e) => eprintln!("Error: Failed to read index directory '{}'. {}", index_path, e),
+    }
+}
+
+/// Runs [`compact`] on the index in `index_path`.
+pub fn run_compact(index_path: &str) {
+    let compacted = Index::open_in_dir(Path::new(index_path)).and_then(|index| compact(&index));
+    match compacted {
+        Ok((before, after)) => {
+            println!("Before: {}", before);
+            println!("After:  {}", after);
+        }
+        Err(e) => eprintln!("Error: Failed to compact index directory '{}'. {}", index_path, e),
+    }
+}
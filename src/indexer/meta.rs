@@ -0,0 +1,72 @@
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::algorithms::pagerank::PageRankConfig;
+
+/// Written next to the tantivy files of an on-disk index.
+pub const META_FILE: &str = "index_meta.json";
+
+/// How an index was built, so the searcher can tell what it is looking at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexMeta {
+    /// Seed URLs of the crawl.
+    pub seeds: Vec<String>,
+    /// Pages the crawl returned, before deduplication and indexability checks.
+    pub pages_crawled: usize,
+    /// Documents that made it into the index.
+    pub documents_indexed: usize,
+    /// `None` when the index was built with `--no-pagerank`.
+    pub pagerank: Option<PageRankMeta>,
+    /// When the crawl ran, in Unix seconds. Each document stores the time its own page was
+    /// fetched.
+    pub crawled_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageRankMeta {
+    #[serde(flatten)]
+    pub config: PageRankConfig,
+    /// Iterations actually run, at most `max_iterations`.
+    pub iterations: usize,
+}
+
+impl IndexMeta {
+    pub fn write(&self, index_dir: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(index_dir.join(META_FILE), json)
+    }
+
+    /// Reads the metadata of the index in `index_dir`. Indexes built before it was
+    /// recorded have none, which is `Ok(None)`.
+    pub fn read(index_dir: &Path) -> io::Result<Option<Self>> {
+        let content = match std::fs::read_to_string(index_dir.join(META_FILE)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&content).map(Some).map_err(io::Error::from)
+    }
+
+    /// One-line overview, e.g. `42 documents from 50 pages crawled 2024-05-01 from 3 seeds,
+    /// PageRank d=0.85 (23 iterations)`.
+    pub fn summary(&self) -> String {
+        let crawled = tantivy::time::OffsetDateTime::from_unix_timestamp(self.crawled_at)
+            .map(|t| t.date().to_string())
+            .unwrap_or_else(|_| self.crawled_at.to_string());
+        let pagerank = match &self.pagerank {
+            Some(pr) => format!("PageRank d={} ({} iterations)", pr.config.damping, pr.iterations),
+            None => "no PageRank".to_string(),
+        };
+        format!(
+            "{} documents from {} pages crawled {} from {} seed{}, {}",
+            self.documents_indexed,
+            self.pages_crawled,
+            crawled,
+            self.seeds.len(),
+            if self.seeds.len() == 1 { "" } else { "s" },
+            pagerank
+        )
+    }
+}
@@ -1,85 +1,733 @@
-use std::collections::HashSet;
-use std::path::Path;
-use tantivy::{doc, Index};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use tracing::{error, info, warn};
+use tantivy::directory::MmapDirectory;
+use tantivy::schema::Schema;
+use tantivy::{doc, Index, IndexWriter, TantivyDocument, TantivyError, Term};
 
 // Declare modules inside the indexer folder
 pub mod schema;
 pub mod algorithms;
+pub mod export;
+pub mod files;
+pub mod maintenance;
+pub mod meta;
+pub mod recrawl;
 
 // Import from siblings and root
-use self::algorithms::pagerank;
+use self::algorithms::pagerank::{self, PageRankConfig};
+use self::meta::{IndexMeta, PageRankMeta};
 use self::schema::WebpageSchema;
-use crate::crawler::Crawler; // <--- Import Crawler from the separate module
+use crate::crawler::{Crawler, Interrupted}; // <--- Import Crawler from the separate module
+use crate::crawler::cache::HttpCache;
+use crate::crawler::config::CrawlConfig;
+use crate::crawler::datascraper::{ScrapeResult, Scraper};
+use crate::crawler::dump::{self, DumpHeader, PageDump};
+use crate::crawler::stats::{CrawlStats, SkipReason};
+use crate::text::Stopwords;
 
-pub async fn run_indexer(index_path: &str) {
-    println!("--- 1. Starting Crawler (Demon Mode) ---");
-    
-    let seed_urls = vec![
-        "https://en.wikipedia.org/wiki/Computer_science",
-        "https://www.rust-lang.org/",
-        "https://news.ycombinator.com/",
-        "https://github.com/rust-lang/rust",
-        "https://stackoverflow.com/questions/tagged/rust"
-    ];
-
-    let page_limit = 500; 
-    let concurrency = 25;
+/// Pages with fewer words than this are usually error pages, login walls or redirects.
+pub const DEFAULT_MIN_WORDS: usize = 50;
+
+/// Heap shared by the tantivy indexing threads. More memory means fewer, larger
+/// segments per commit (faster indexing and merges); less suits small machines.
+pub const DEFAULT_WRITER_MEMORY_MB: usize = 200;
+/// tantivy needs at least 15 MB per indexing thread.
+pub const MIN_WRITER_MEMORY_MB: usize = 15;
+/// tantivy caps each thread's budget just below 4 GB; staying under that is always valid.
+pub const MAX_WRITER_MEMORY_MB: usize = 4_000;
+
+/// Pages processed per indexing batch. Each batch ends with a commit that makes progress durable.
+pub const DEFAULT_COMMIT_EVERY: usize = 1000;
+
+/// Inbound link texts indexed per page. Navigation links repeat the same text on every
+/// page of a site, so more rarely adds anything.
+pub const MAX_ANCHORS_PER_PAGE: usize = 100;
+
+/// Tunables for a single `index` run.
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    /// Seeds, limits and scope of the crawl.
+    pub crawl: CrawlConfig,
+    /// Skip documents whose body has fewer words than this.
+    pub min_words: usize,
+    /// Words dropped by the tokenizer. The searcher must be given the same list.
+    pub stopwords: Stopwords,
+    /// Also write the link graph to this GraphViz file.
+    pub export_dot: Option<String>,
+    /// Crawl at most this many pages from a single site, see [`Crawler::with_max_pages_per_domain`].
+    pub max_pages_per_domain: Option<usize>,
+    /// Index writer heap in megabytes, see [`DEFAULT_WRITER_MEMORY_MB`].
+    pub writer_memory_mb: usize,
+    /// Commit after this many documents so a crash only loses the current batch.
+    pub commit_every: usize,
+    /// Crawl and return the [`CrawlStats`] from [`run_indexer`] without touching the index.
+    pub dry_run: bool,
+    /// Compute PageRank over crawled pages only, see [`pagerank::prune_to_crawled`].
+    /// Off by default: uncrawled link targets take part as dangling nodes.
+    pub prune_to_crawled: bool,
+    /// JSON file of ETag/Last-Modified validators and page content from earlier crawls,
+    /// used for conditional GETs and updated after the crawl.
+    pub http_cache: Option<String>,
+    /// Draw progress bars for the crawl and indexing phases.
+    pub show_progress: bool,
+    /// Index the meta description of paywalled pages, flagged as `partial` and ranked
+    /// lower, instead of dropping them. Such pages are exempt from `min_words`.
+    pub index_partial: bool,
+    /// Compute PageRank. When off, every page gets a PageRank of 0 and the searcher
+    /// ignores it, which saves the ranking pass when only testing relevance.
+    pub pagerank: bool,
+    /// Damping and iteration limits for PageRank.
+    pub pagerank_config: PageRankConfig,
+    /// Also seed the crawl with every URL of the existing index, so a recrawl refreshes
+    /// the indexed pages as well as following new links. Pairs well with `http_cache`.
+    pub append_seeds_from_index: bool,
+    /// Write crawled pages to this NDJSON file as they're fetched and build the index from
+    /// it with [`index_dump`], instead of holding every page in memory until the crawl ends.
+    pub dump: Option<String>,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            crawl: CrawlConfig::default(),
+            min_words: DEFAULT_MIN_WORDS,
+            stopwords: Stopwords::default(),
+            export_dot: None,
+            max_pages_per_domain: None,
+            writer_memory_mb: DEFAULT_WRITER_MEMORY_MB,
+            commit_every: DEFAULT_COMMIT_EVERY,
+            dry_run: false,
+            prune_to_crawled: false,
+            http_cache: None,
+            show_progress: true,
+            index_partial: false,
+            pagerank: true,
+            pagerank_config: PageRankConfig::default(),
+            append_seeds_from_index: false,
+            dump: None,
+        }
+    }
+}
+
+impl IndexerConfig {
+    /// Checks settings that would otherwise only fail after the crawl finished.
+    pub fn validate(&self) -> Result<(), String> {
+        self.crawl.validate()?;
+        if !(MIN_WRITER_MEMORY_MB..=MAX_WRITER_MEMORY_MB).contains(&self.writer_memory_mb) {
+            return Err(format!(
+                "Writer memory must be between {} and {} MB, got {} MB.",
+                MIN_WRITER_MEMORY_MB, MAX_WRITER_MEMORY_MB, self.writer_memory_mb
+            ));
+        }
+        if self.commit_every == 0 {
+            return Err("Commit batch size must be at least 1.".to_string());
+        }
+        self.pagerank_config.validate()?;
+        Ok(())
+    }
+}
+
+/// Crawls and indexes into `index_path`, logging failures. Returns the crawl's stats on a
+/// [`dry_run`](IndexerConfig::dry_run), for the caller to report, and fails only when a
+/// second Ctrl-C cancels the crawl.
+pub async fn run_indexer(index_path: &str, config: &IndexerConfig) -> Result<Option<Arc<CrawlStats>>, Interrupted> {
+    if let Err(e) = config.validate() {
+        error!("Invalid configuration: {}", e);
+        return Ok(None);
+    }
+
+    info!("--- 1. Starting Crawler (Demon Mode) ---");
     
+    // The seeds actually crawled, so they are also what the index metadata records
+    let mut config = config.clone();
+    if config.append_seeds_from_index {
+        match recrawl::stored_urls_in(Path::new(index_path)) {
+            Ok(stored) => {
+                let seeds = recrawl::merge_seeds(&config.crawl.seeds, &stored);
+                info!("Revisiting {} indexed pages ({} seeds in total).", stored.len(), seeds.len());
+                if seeds.len() > config.crawl.limit {
+                    warn!("Only {} of {} seeds fit the crawl limit; raise --limit to refresh them all.", config.crawl.limit, seeds.len());
+                }
+                config.crawl.seeds = seeds;
+            }
+            Err(e) => {
+                error!("Failed to read URLs from the index in '{}': {}", index_path, e);
+                return Ok(None);
+            }
+        }
+    }
+    let config = &config;
+    let crawl = &config.crawl;
+    let seed_urls: Vec<&str> = crawl.seeds.iter().map(String::as_str).collect();
+
+    let mut scraper = Scraper::builder();
+    if let Some(user_agent) = &crawl.user_agent {
+        scraper = scraper.user_agent(user_agent.clone());
+    }
+    if let Some(timeout) = crawl.request_timeout {
+        scraper = scraper.request_timeout(timeout);
+    }
+    scraper = scraper
+        .link_sources(crawl.link_sources.clone())
+        .danger_accept_invalid_certs(crawl.danger_accept_invalid_certs)
+        .respect_env_proxy(crawl.respect_env_proxy)
+        .record_redirects(crawl.record_redirects);
+    let blocklist = match crawl.blocklist() {
+        Ok(blocklist) => blocklist,
+        Err(e) => {
+            error!("{}", e);
+            return Ok(None);
+        }
+    };
+    let language_filter = match crawl.header_map().and_then(|headers| Ok((headers, crawl.language_detector()?, crawl.language_filter()?))) {
+        Ok((headers, language, language_filter)) => {
+            scraper = scraper.headers(headers).language_detector(language);
+            language_filter
+        }
+        Err(e) => {
+            error!("Invalid configuration: {}", e);
+            return Ok(None);
+        }
+    };
+    let http_cache = match &config.http_cache {
+        Some(path) => match HttpCache::load(Path::new(path)) {
+            Ok(cache) => {
+                info!("Loaded {} cached pages from '{}'.", cache.len(), path);
+                scraper = scraper.http_cache(cache.clone());
+                Some(cache)
+            }
+            Err(e) => {
+                error!("Failed to load HTTP cache '{}': {}", path, e);
+                return Ok(None);
+            }
+        },
+        None => None,
+    };
+    let scraper = match scraper.build() {
+        Ok(scraper) => scraper,
+        Err(e) => {
+            error!("Invalid scraper configuration: {}", e);
+            return Ok(None);
+        }
+    };
+
+    let header = DumpHeader { seeds: crawl.seeds.clone(), crawled_at: unix_now() };
+    let dump = match &config.dump {
+        Some(path) => match PageDump::create(Path::new(path), &header) {
+            Ok(dump) => Some(dump),
+            Err(e) => {
+                error!("Failed to create page dump '{}': {}", path, e);
+                return Ok(None);
+            }
+        },
+        None => None,
+    };
+
     // Create Crawler from the crate::crawler module
-    let mut crawler = Crawler::new(&seed_urls);
+    let mut crawler = Crawler::new(&seed_urls)
+        .with_scraper(scraper)
+        .with_allowed_domains(crawl.allowed_domains.clone())
+        .with_denied_paths(crawl.denied_paths.clone())
+        .with_path_prefixes(crawl.path_prefix_filter.clone())
+        .with_politeness_delay(Duration::from_millis(crawl.politeness_delay_ms))
+        .with_max_requests_per_second(crawl.max_requests_per_second)
+        .with_focus(crawl.focus())
+        .with_language_filter(language_filter)
+        .with_blocklist(blocklist)
+        .with_max_duration(crawl.max_duration)
+        .with_max_pages_per_domain(config.max_pages_per_domain)
+        .with_progress(config.show_progress)
+        .with_partial_pages(config.index_partial)
+        .with_page_dump(dump);
 
-    let scraped_data = match crawler.crawl(page_limit, concurrency).await {
-        Ok(data) => {
-            println!("Crawler finished. Collected {} pages.", data.len());
-            data
+    let (scraped_data, stats) = match crawler.crawl(crawl.limit, crawl.concurrency).await {
+        Ok((data, stats)) => {
+            match &config.dump {
+                Some(path) => info!("Crawler finished. Wrote {} pages to '{}'.", stats.pages_fetched(), path),
+                None => info!("Crawler finished. Collected {} pages.", data.len()),
+            }
+            (data, stats)
         },
         Err(e) => {
-            eprintln!("Crawler fatal error: {}", e);
-            return;
+            if let Some(&interrupted) = e.downcast_ref::<Interrupted>() {
+                return Err(interrupted);
+            }
+            error!("Crawler fatal error: {}", e);
+            return Ok(None);
         }
     };
 
+    if let (Some(cache), Some(path)) = (&http_cache, &config.http_cache) {
+        match cache.save(Path::new(path)) {
+            Ok(()) => info!("Saved {} cached pages to '{}'.", cache.len(), path),
+            Err(e) => warn!("Failed to save HTTP cache '{}': {}", path, e),
+        }
+    }
+
+    if config.dry_run {
+        let dumped = config.dump.iter().flat_map(|path| dump::read_pages(Path::new(path)).into_iter().flatten().flatten());
+        for page in scraped_data.into_iter().chain(dumped) {
+            if let Err(skip) = check_indexable(&page, config.min_words) {
+                stats.record_skip(skip.reason());
+            }
+        }
+        info!("--- Dry run: nothing was written to '{}' ---", index_path);
+        return Ok(Some(stats));
+    }
+
+    let backend = IndexBackend::Disk(index_path.into());
+    let indexed = match &config.dump {
+        Some(path) => index_dump(Path::new(path), &backend, config),
+        None => index_pages(scraped_data, &backend, config),
+    };
+    if let Err(e) = indexed {
+        error!("Indexing failed: {}", e);
+    }
+    Ok(None)
+}
+
+/// Where [`index_pages`] puts the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexBackend {
+    /// Memory-mapped files in this directory, updating the index already there. An index
+    /// with another schema is an error rather than being replaced.
+    Disk(PathBuf),
+    /// A `RamDirectory`: nothing touches the disk and the index is gone once dropped.
+    /// Meant for tests and throwaway crawls.
+    Memory,
+}
+
+impl IndexBackend {
+    fn open(&self, schema: Schema) -> tantivy::Result<Index> {
+        let index_dir = match self {
+            IndexBackend::Memory => return Ok(Index::create_in_ram(schema)),
+            IndexBackend::Disk(dir) => dir,
+        };
+        std::fs::create_dir_all(index_dir)?;
+        // An index built with an older schema can't be reused. Wiping it here would leave
+        // nothing behind if the run then fails, so the user picks what to do with it.
+        Index::open_or_create(MmapDirectory::open(index_dir)?, schema).map_err(|e| match e {
+            TantivyError::SchemaError(e) => TantivyError::SchemaError(format!(
+                "the index in '{}' has an incompatible schema ({}). Delete it or index into another directory",
+                index_dir.display(),
+                e
+            )),
+            e => e,
+        })
+    }
+}
+
+/// Ranks `pages` with PageRank and indexes the indexable ones into `backend`, replacing
+/// its previous contents. On disk, an [`IndexMeta`] describing the run is written next
+/// to the index. Returns the index with the tokenizer registered, ready to be
+/// searched with [`Searcher::from_index`](crate::searcher::Searcher::from_index).
+pub fn index_pages(pages: Vec<ScrapeResult>, backend: &IndexBackend, config: &IndexerConfig) -> tantivy::Result<Index> {
     // --- 2. Calculate PageRank ---
-    println!("\n--- 2. Calculating PageRank ---");
     // We map the scraped data into a format PageRank understands
-    let link_graph: pagerank::LinkGraph = scraped_data
+    let link_graph: pagerank::LinkGraph = pages
         .iter()
         .map(|data| (data.url.clone(), data.links.iter().cloned().collect::<HashSet<String>>()))
         .collect();
+    let pages_crawled = pages.len();
+    let (page_ranks, pagerank_meta) = rank_pages(link_graph, config);
+
+    let pages = dedup_by_content(pages, &page_ranks, config.min_words);
+    let anchors = inbound_anchors(&pages);
+    let ranking = Ranking { page_ranks, pagerank_meta, anchors, pages_crawled, seeds: config.crawl.seeds.clone(), crawled_at: unix_now() };
+    let total = pages.len();
+    write_index(pages.into_iter().map(Ok), total, &ranking, backend, config)
+}
+
+/// Like [`index_pages`], but reads the pages from a [`PageDump`] file, so they never all
+/// are in memory at once. The [`IndexMeta`] records the seeds and time of the crawl that
+/// wrote the dump; dumps without a [`DumpHeader`] fall back to `config`'s seeds and now.
+///
+/// The file is read twice: first for the link graph, inbound link texts and content
+/// hashes, which PageRank and deduplication need up front, then again to index the pages
+/// batch by batch. Only those per-page summaries are held for the whole run, not the bodies.
+pub fn index_dump(dump_path: &Path, backend: &IndexBackend, config: &IndexerConfig) -> tantivy::Result<Index> {
+    let header = dump::read_header(dump_path)?
+        .unwrap_or_else(|| DumpHeader { seeds: config.crawl.seeds.clone(), crawled_at: unix_now() });
+    let mut link_graph = pagerank::LinkGraph::new();
+    let mut anchors: HashMap<String, Vec<String>> = HashMap::new();
+    let mut hashes = Vec::new();
+    let mut pages_crawled = 0;
+    for page in dump::read_pages(dump_path)? {
+        let page = page?;
+        pages_crawled += 1;
+        add_inbound_anchors(&mut anchors, &page);
+        if check_indexable(&page, config.min_words).is_ok() {
+            hashes.push((content_hash(&page.body_text), page.url.clone()));
+        }
+        link_graph.insert(page.url, page.links.into_iter().collect());
+    }
+    info!("Read {} pages from '{}'.", pages_crawled, dump_path.display());
+    let (page_ranks, pagerank_meta) = rank_pages(link_graph, config);
 
-    let page_ranks = pagerank::calculate_pagerank(&link_graph);
-    println!("PageRank calculation complete.");
+    // The copy kept of each body, chosen like `dedup_by_content` does
+    let rank = |url: &str| page_ranks.get(url).copied().unwrap_or(0.0);
+    let mut kept: HashMap<[u8; 32], String> = HashMap::new();
+    let mut duplicates = 0;
+    for (hash, url) in hashes {
+        match kept.get_mut(&hash) {
+            Some(other) => {
+                duplicates += 1;
+                if rank(&url) > rank(other) {
+                    *other = url;
+                }
+            }
+            None => {
+                kept.insert(hash, url);
+            }
+        }
+    }
+    if duplicates > 0 {
+        info!("Skipped {} pages with duplicate content.", duplicates);
+    }
+
+    let ranking = Ranking { page_ranks, pagerank_meta, anchors, pages_crawled, seeds: header.seeds, crawled_at: header.crawled_at };
+    let min_words = config.min_words;
+    let pages = dump::read_pages(dump_path)?.filter_map(|page| match page {
+        Ok(page) if check_indexable(&page, min_words).is_ok() => {
+            let hash = content_hash(&page.body_text);
+            (kept.get(&hash) == Some(&page.url)).then_some(Ok(page))
+        }
+        // Unindexable pages are skipped (and logged) when building their documents
+        Ok(page) => Some(Ok(page)),
+        Err(e) => Some(Err(e.into())),
+    });
+    write_index(pages, pages_crawled - duplicates, &ranking, backend, config)
+}
+
+/// Runs [`index_dump`] into the index directory at `index_path`.
+pub fn run_dump_indexer(index_path: &str, dump_path: &str, config: &IndexerConfig) {
+    if let Err(e) = config.validate() {
+        error!("Invalid configuration: {}", e);
+        return;
+    }
+    if let Err(e) = index_dump(Path::new(dump_path), &IndexBackend::Disk(index_path.into()), config) {
+        error!("Indexing '{}' failed: {}", dump_path, e);
+    }
+}
+
+/// What the whole crawl contributes to each page's document.
+struct Ranking {
+    page_ranks: pagerank::PageRanks,
+    pagerank_meta: Option<PageRankMeta>,
+    anchors: HashMap<String, Vec<String>>,
+    pages_crawled: usize,
+    seeds: Vec<String>,
+    /// When the crawl ran, in Unix seconds.
+    crawled_at: i64,
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+/// PageRank of every page in `link_graph` (all 0 with PageRank off), also writing the
+/// graph to `config.export_dot` if set.
+fn rank_pages(link_graph: pagerank::LinkGraph, config: &IndexerConfig) -> (pagerank::PageRanks, Option<PageRankMeta>) {
+    let link_graph = if config.prune_to_crawled { pagerank::prune_to_crawled(&link_graph) } else { link_graph };
+    let (page_ranks, pagerank_meta) = if config.pagerank {
+        info!("--- 2. Calculating PageRank ---");
+        let started = Instant::now();
+        let (page_ranks, iterations) = pagerank::calculate_pagerank_with(&link_graph, &config.pagerank_config);
+        info!(
+            "PageRank calculation complete in {:.2}s ({} iterations, damping {}).",
+            started.elapsed().as_secs_f64(),
+            iterations,
+            config.pagerank_config.damping
+        );
+        (page_ranks, Some(PageRankMeta { config: config.pagerank_config, iterations }))
+    } else {
+        info!("--- 2. Skipping PageRank: every page gets a PageRank of 0 ---");
+        (pagerank::PageRanks::new(), None)
+    };
+
+    if let Some(dot_path) = &config.export_dot {
+        let ranks = config.pagerank.then_some(&page_ranks);
+        match export::export_link_graph_dot(&link_graph, ranks, dot_path) {
+            Ok(()) => info!("Link graph written to '{}'.", dot_path),
+            Err(e) => warn!("Failed to write link graph to '{}': {}", dot_path, e),
+        }
+    }
+    (page_ranks, pagerank_meta)
+}
+
+/// Indexes `pages` into `backend` in batches of `config.commit_every`, replacing its
+/// previous contents page by page, and writes the [`IndexMeta`]. `total` only sizes the
+/// progress bar.
+fn write_index(
+    pages: impl Iterator<Item = tantivy::Result<ScrapeResult>>,
+    total: usize,
+    ranking: &Ranking,
+    backend: &IndexBackend,
+    config: &IndexerConfig,
+) -> tantivy::Result<Index> {
+    let Ranking { page_ranks, pagerank_meta, anchors, pages_crawled, seeds, crawled_at } = ranking;
 
     // --- 3. Build Index ---
-    println!("\n--- 3. Indexing to '{}' ---", index_path);
+    match backend {
+        IndexBackend::Disk(dir) => info!("--- 3. Indexing to '{}' ---", dir.display()),
+        IndexBackend::Memory => info!("--- 3. Indexing in memory ---"),
+    }
 
     let (schema, fields) = WebpageSchema::build();
-    let index_dir = Path::new(index_path);
-    
-    if !index_dir.exists() {
-        std::fs::create_dir_all(index_dir).expect("Failed to create index dir");
+    let index = backend.open(schema)?;
+
+    WebpageSchema::register_tokenizer(&index, &config.stopwords);
+
+    let mut index_writer: IndexWriter = index.writer(config.writer_memory_mb * 1_000_000)?;
+    // Each page replaces its old document in the commit of its batch, and documents of pages
+    // not indexed again go once every batch is in. A crash midway leaves the previous index
+    // with the batches committed so far, rather than only those batches.
+    let mut stale: HashSet<String> = recrawl::stored_urls(&index)?.into_iter().collect();
+
+    let indexed = AtomicUsize::new(0);
+    let mut processed = 0;
+    let started = Instant::now();
+    let progress = crate::progress::bar(total as u64, "pages", config.show_progress);
+
+    // `add_document` takes `&self`, so each batch is built and fed to the writer from the
+    // rayon pool. Document order doesn't matter: scores don't depend on it.
+    let mut pages = pages.peekable();
+    while pages.peek().is_some() {
+        let batch = pages.by_ref().take(config.commit_every).collect::<tantivy::Result<Vec<_>>>()?;
+        processed += batch.len();
+        // Redirects can land two pages of a batch on one URL. Their deletes and adds would race
+        // on the pool, so only the last one is kept, as a later batch would replace it.
+        let fetched = batch.len();
+        let mut seen = HashSet::new();
+        let batch: Vec<_> = batch.into_iter().rev().filter(|page| seen.insert(page.url.clone())).collect();
+        progress.inc((fetched - batch.len()) as u64);
+
+        let urls = batch
+            .into_par_iter()
+            .map(|result| {
+                let url = result.url.clone();
+                let mut written = None;
+                if let Some(mut document) = build_document(result, &fields, page_ranks, *crawled_at, config.min_words) {
+                    for text in anchors.get(&url).into_iter().flatten() {
+                        document.add_text(fields.anchor_text, text);
+                    }
+                    index_writer.delete_term(Term::from_field_text(fields.url, &url));
+                    index_writer.add_document(document)?;
+                    indexed.fetch_add(1, Ordering::Relaxed);
+                    written = Some(url);
+                }
+                progress.inc(1);
+                Ok::<_, TantivyError>(written)
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+
+        index_writer.commit()?;
+        for url in urls.iter().flatten() {
+            stale.remove(url);
+        }
+        info!("Committed {} documents ({}/{} pages processed).", indexed.load(Ordering::Relaxed), processed, total);
+    }
+
+    progress.finish_and_clear();
+    if !stale.is_empty() {
+        for url in &stale {
+            index_writer.delete_term(Term::from_field_text(fields.url, url));
+        }
+        index_writer.commit()?;
+        info!("Removed {} documents of pages that weren't indexed again.", stale.len());
+    }
+    let indexed = indexed.into_inner();
+    let elapsed = started.elapsed().as_secs_f64();
+    info!(
+        "Indexing complete. {} documents indexed in {:.2}s ({:.0} docs/s).",
+        indexed,
+        elapsed,
+        if elapsed > 0.0 { indexed as f64 / elapsed } else { 0.0 }
+    );
+
+    if let IndexBackend::Disk(dir) = backend {
+        let meta = IndexMeta {
+            seeds: seeds.clone(),
+            pages_crawled: *pages_crawled,
+            documents_indexed: indexed,
+            pagerank: pagerank_meta.clone(),
+            crawled_at: *crawled_at,
+        };
+        if let Err(e) = meta.write(dir) {
+            warn!("Failed to write {}: {}", meta::META_FILE, e);
+        }
+    }
+    Ok(index)
+}
+
+/// Link texts pointing to each URL from the other crawled pages, at most
+/// [`MAX_ANCHORS_PER_PAGE`] per target. Links from a page to itself don't count.
+fn inbound_anchors(pages: &[ScrapeResult]) -> HashMap<String, Vec<String>> {
+    let mut anchors: HashMap<String, Vec<String>> = HashMap::new();
+    for page in pages {
+        add_inbound_anchors(&mut anchors, page);
     }
+    anchors
+}
 
-    let index = Index::open_or_create(tantivy::directory::MmapDirectory::open(index_dir).unwrap(), schema.clone())
-        .expect("Failed to open index");
+fn add_inbound_anchors(anchors: &mut HashMap<String, Vec<String>>, page: &ScrapeResult) {
+    for (target, text) in &page.anchors {
+        if *target == page.url {
+            continue;
+        }
+        let texts = anchors.entry(target.clone()).or_default();
+        if texts.len() < MAX_ANCHORS_PER_PAGE && !texts.contains(text) {
+            texts.push(text.clone());
+        }
+    }
+}
+
+/// SHA-256 of the lowercased body with whitespace collapsed, so trivially reformatted
+/// copies (mirrors, CDN copies) hash the same.
+fn content_hash(body_text: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for (i, word) in body_text.split_whitespace().enumerate() {
+        if i > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.to_lowercase().as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Drops pages whose body is identical to another indexable page's, keeping the copy
+/// with the higher PageRank. Pages that won't be indexed anyway don't take part, so a
+/// `noindex` mirror can't crowd out the real page.
+fn dedup_by_content(pages: Vec<ScrapeResult>, page_ranks: &pagerank::PageRanks, min_words: usize) -> Vec<ScrapeResult> {
+    let rank = |page: &ScrapeResult| page_ranks.get(&page.url).copied().unwrap_or(0.0);
+    let mut kept: Vec<Option<ScrapeResult>> = Vec::with_capacity(pages.len());
+    let mut seen: HashMap<[u8; 32], usize> = HashMap::new();
+    let mut duplicates = 0;
 
-    WebpageSchema::register_tokenizer(&index);
+    for page in pages {
+        if check_indexable(&page, min_words).is_err() {
+            kept.push(Some(page));
+            continue;
+        }
+        let hash = content_hash(&page.body_text);
+        let Some(&slot) = seen.get(&hash) else {
+            seen.insert(hash, kept.len());
+            kept.push(Some(page));
+            continue;
+        };
+        duplicates += 1;
+        let other = kept[slot].as_ref().expect("seen slots hold a page");
+        if rank(&page) > rank(other) {
+            info!("[DUP] skipping {} (same content as {})", other.url, page.url);
+            kept[slot] = Some(page);
+        } else {
+            info!("[DUP] skipping {} (same content as {})", page.url, other.url);
+        }
+    }
 
-    let mut index_writer = index.writer(200_000_000).expect("Failed to create writer");
-    index_writer.delete_all_documents().expect("Failed to clear old index");
+    if duplicates > 0 {
+        info!("Skipped {} pages with duplicate content.", duplicates);
+    }
+    kept.into_iter().flatten().collect()
+}
 
-    for result in scraped_data {
-        let pr_score = page_ranks.get(&result.url).cloned().unwrap_or(0.0);
+/// Why a crawled page is kept out of the index.
+enum Skip {
+    /// The page asked not to be indexed (robots meta tag or `X-Robots-Tag`).
+    NoIndex,
+    /// The body has fewer words than the configured minimum.
+    Thin(usize),
+    /// An error page served with HTTP 200.
+    Soft404,
+}
+
+impl Skip {
+    fn reason(&self) -> SkipReason {
+        match self {
+            Skip::NoIndex => SkipReason::Robots,
+            Skip::Thin(_) => SkipReason::ThinContent,
+            Skip::Soft404 => SkipReason::Soft404,
+        }
+    }
+}
 
-        index_writer.add_document(doc!(
-            fields.url => result.url,
-            fields.title => result.title.unwrap_or_default(),
-            fields.body => result.body_text,
-            fields.pagerank => pr_score,
-            fields.language => result.language
-        )).expect("Failed to add doc");
+/// The page's word count if it should be indexed.
+fn check_indexable(result: &ScrapeResult, min_words: usize) -> Result<usize, Skip> {
+    if result.no_index {
+        return Err(Skip::NoIndex);
     }
+    if result.likely_404 {
+        return Err(Skip::Soft404);
+    }
+    let word_count = result.body_text.split_whitespace().count();
+    // A meta description is always short; only pages kept on purpose are partial here
+    if word_count < min_words && !result.is_partial {
+        return Err(Skip::Thin(word_count));
+    }
+    Ok(word_count)
+}
+
+/// Turns a scraped page into a tantivy document, or `None` if the page shouldn't be indexed.
+/// `crawled_at` is only stored for pages without a [`ScrapeResult::fetched_at`].
+fn build_document(
+    result: ScrapeResult,
+    fields: &WebpageSchema,
+    page_ranks: &pagerank::PageRanks,
+    crawled_at: i64,
+    min_words: usize,
+) -> Option<TantivyDocument> {
+    let word_count = match check_indexable(&result, min_words) {
+        Ok(word_count) => word_count,
+        Err(Skip::NoIndex) => {
+            info!(url = %result.url, "skipping page: noindex");
+            return None;
+        }
+        Err(Skip::Thin(word_count)) => {
+            info!(url = %result.url, words = word_count, "skipping page: thin content");
+            return None;
+        }
+        Err(Skip::Soft404) => {
+            info!(url = %result.url, "skipping page: soft 404");
+            return None;
+        }
+    };
 
-    index_writer.commit().expect("Commit failed");
-    println!("Indexing complete.");
+    let pr_score = page_ranks.get(&result.url).cloned().unwrap_or(0.0);
+    let crawled_at = result.fetched_at.unwrap_or(crawled_at);
+
+    let mut document = doc!(
+        fields.url => result.url,
+        fields.title => result.title.unwrap_or_default(),
+        fields.body => result.body_text,
+        fields.pagerank => pr_score,
+        fields.language => result.language,
+        fields.crawled_at => crawled_at,
+        fields.word_count => word_count as i64,
+        fields.partial => result.is_partial
+    );
+    if let Some(author) = result.structured.author {
+        document.add_text(fields.author, author);
+    }
+    if let Some(favicon) = result.favicon {
+        document.add_text(fields.favicon, favicon);
+    }
+    for schema_type in result.structured.types {
+        document.add_text(fields.schema_type, schema_type);
+    }
+    if let Some(status) = result.status {
+        document.add_i64(fields.status, i64::from(status));
+    }
+    if let Some(chain) = result.redirected_from {
+        document.add_i64(fields.redirects, chain.len() as i64);
+        for url in chain {
+            document.add_text(fields.redirected_from, url);
+        }
+    }
+    Some(document)
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use tantivy::collector::DocSetCollector;
+use tantivy::query::AllQuery;
+use tantivy::schema::Value;
+use tantivy::{Index, TantivyDocument, TantivyError};
+
+use crate::crawler::datascraper::normalize_link;
+
+/// URLs of every document in `index`, sorted.
+pub fn stored_urls(index: &Index) -> tantivy::Result<Vec<String>> {
+    let url_field = index
+        .schema()
+        .get_field("url")
+        .map_err(|_| TantivyError::SchemaError("index has no 'url' field".to_string()))?;
+    let searcher = index.reader()?.searcher();
+    let mut urls = Vec::new();
+    for address in searcher.search(&AllQuery, &DocSetCollector)? {
+        let doc: TantivyDocument = searcher.doc(address)?;
+        if let Some(url) = doc.get_first(url_field).and_then(|v| v.as_str()) {
+            urls.push(url.to_string());
+        }
+    }
+    urls.sort();
+    Ok(urls)
+}
+
+/// [`stored_urls`] of the index in `index_dir`. No index there yet is an empty list.
+pub fn stored_urls_in(index_dir: &Path) -> tantivy::Result<Vec<String>> {
+    if !index_dir.join("meta.json").exists() {
+        return Ok(Vec::new());
+    }
+    stored_urls(&Index::open_in_dir(index_dir)?)
+}
+
+/// `seeds` followed by the `stored` URLs not already among them. Everything is brought
+/// into the form the scraper gives links ([`normalize_link`]), so a page reached both
+/// as a seed and through a link is only fetched once, and its refreshed copy replaces
+/// the indexed one instead of sitting next to it.
+pub fn merge_seeds(seeds: &[String], stored: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    seeds
+        .iter()
+        .chain(stored)
+        .filter_map(|url| normalize_link(url))
+        .filter(|url| seen.insert(url.clone()))
+        .collect()
+}
@@ -1,5 +1,7 @@
 use tantivy::schema::*;
-use tantivy::tokenizer::{TextAnalyzer, SimpleTokenizer, LowerCaser, Stemmer, Language};
+use tantivy::tokenizer::{TextAnalyzer, SimpleTokenizer, LowerCaser, Stemmer, StopWordFilter, Language};
+
+use crate::text::Stopwords;
 
 pub struct WebpageSchema {
     pub url: Field,
@@ -7,6 +9,16 @@ pub struct WebpageSchema {
     pub body: Field,
     pub pagerank: Field,
     pub language: Field, // Stores "en", "pl", "de", etc.
+    pub crawled_at: Field, // Unix seconds
+    pub author: Field,
+    pub schema_type: Field, // JSON-LD @type, e.g. "Article"
+    pub word_count: Field, // Words in the body
+    pub favicon: Field, // Site icon URL, for display only
+    pub partial: Field, // Only the metadata of a paywalled page was indexed
+    pub anchor_text: Field, // Text of links pointing to the page
+    pub status: Field, // HTTP status of the final response
+    pub redirects: Field, // Redirects followed to reach the page, if they were recorded
+    pub redirected_from: Field, // URLs of those redirects, for display only
 }
 
 impl WebpageSchema {
@@ -27,7 +39,7 @@ impl WebpageSchema {
         let title = schema_builder.add_text_field("title", title_options);
 
         // Body: Indexed but NOT stored (saves disk space). Searchable.
-        let body = schema_builder.add_text_field("body", text_options);
+        let body = schema_builder.add_text_field("body", text_options.clone());
         
         // PageRank: FastField (f64) for mathematical scoring
         let pagerank = schema_builder.add_f64_field("pagerank", FAST | STORED);
@@ -35,6 +47,32 @@ impl WebpageSchema {
         // Language: Stored String for filtering (e.g., "language:en")
         let language = schema_builder.add_text_field("language", STRING | STORED);
 
+        // Crawled At: Unix timestamp (seconds). FastField so results can be sorted by recency
+        let crawled_at = schema_builder.add_i64_field("crawled_at", FAST | STORED);
+
+        // Author and Type: from JSON-LD structured data. Author is searchable text,
+        // type is an exact-match string (e.g. "schema_type:Article"), one value per type.
+        let author = schema_builder.add_text_field("author", text_options.clone().set_stored());
+        let schema_type = schema_builder.add_text_field("schema_type", STRING | STORED);
+
+        // Word Count: body length in words. FastField so `words:>500` range filters are cheap
+        let word_count = schema_builder.add_i64_field("word_count", FAST | STORED);
+
+        // Favicon: stored for result display, not indexed
+        let favicon = schema_builder.add_text_field("favicon", STORED);
+
+        // Partial: FastField so the searcher can down-weight metadata-only pages while scoring
+        let partial = schema_builder.add_bool_field("partial", FAST | STORED);
+
+        // Anchor Text: what other pages call this one when linking to it. Searchable, not stored
+        let anchor_text = schema_builder.add_text_field("anchor_text", text_options);
+
+        // Status and Redirects: FastFields for `status:200` and `redirects:>0` filters.
+        // The redirect chain itself is stored for display, not indexed
+        let status = schema_builder.add_i64_field("status", FAST | STORED);
+        let redirects = schema_builder.add_i64_field("redirects", FAST | STORED);
+        let redirected_from = schema_builder.add_text_field("redirected_from", STORED);
+
         let schema = schema_builder.build();
         
         let fields = Self {
@@ -43,15 +81,32 @@ impl WebpageSchema {
             body,
             pagerank,
             language,
+            crawled_at,
+            author,
+            schema_type,
+            word_count,
+            favicon,
+            partial,
+            anchor_text,
+            status,
+            redirects,
+            redirected_from,
         };
 
         (schema, fields)
     }
 
-    /// Register the "en_stem" tokenizer logic
-    pub fn register_tokenizer(index: &tantivy::Index) {
+    /// Register the "en_stem" tokenizer logic.
+    /// The indexer and searcher must use the same stopwords, otherwise query terms and
+    /// phrase positions won't line up with the indexed tokens.
+    ///
+    /// Unlike [`count_words`](crate::crawler::datascraper::count_words), this splits
+    /// "gpt-4" into "gpt" and "4": a query for "gpt-4" becomes that phrase and still finds
+    /// it, and "covid" also finds "covid-19".
+    pub fn register_tokenizer(index: &tantivy::Index, stopwords: &Stopwords) {
         let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
             .filter(LowerCaser)
+            .filter(StopWordFilter::remove(stopwords.words().cloned()))
             .filter(Stemmer::new(Language::English))
             .build();
             
@@ -1,3 +1,5 @@
 pub mod crawler;
 pub mod indexer;
+pub mod progress;
 pub mod searcher;
+pub mod text;
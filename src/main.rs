@@ -1,13 +1,22 @@
 use std::env;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
 // Use the public modules from our library crate.
 // Replace `mini_search_engine` with the actual name of your project from Cargo.toml.
-use search_enginge::{indexer, searcher};
+use search_enginge::{crawler, indexer, searcher};
+use search_enginge::crawler::config::{parse_duration, read_seed_file, CrawlConfig, DEFAULT_CONFIG_FILE};
+use search_enginge::crawler::datascraper::{ScrapeResult, Scraper};
+use search_enginge::text::Stopwords;
+use tracing::info;
 
-// The Crawler module is a dependency for the indexer, but main.rs doesn't call it directly,
-// so we don't need to `use` it here.
 
 // A single constant for the application's configuration.
 const INDEX_PATH: &str = "./search_index";
+/// Characters of body text printed by `scrape`.
+const SCRAPE_PREVIEW_CHARS: usize = 500;
+/// Exit status of a process stopped by Ctrl-C, as shells report it.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
 
 /// The main entry point, which dispatches to the correct command module.
 #[tokio::main]
@@ -15,19 +24,562 @@ async fn main() {
     let args: Vec<String> = env::args().collect();
     // Use the first argument as the command, defaulting to "search".
     let command = args.get(1).map_or("search", |s| s.as_str());
+    init_logging(&args);
 
     match command {
-        "index" => indexer::run_indexer(INDEX_PATH).await,
-        "search" => searcher::run_searcher(INDEX_PATH),
+        "index" => {
+            let mut config = indexer::IndexerConfig { crawl: load_crawl_config(&args), ..Default::default() };
+            if let Some(seeds) = seeds_from_args(&args) {
+                config.crawl.seeds = seeds;
+            }
+            if let Some(limit) = parse_flag(&args, "--limit") {
+                config.crawl.limit = limit;
+            }
+            if let Some(concurrency) = parse_flag(&args, "--concurrency") {
+                config.crawl.concurrency = concurrency;
+            }
+            if let Some(user_agent) = flag_value(&args, "--user-agent") {
+                config.crawl.user_agent = Some(user_agent.to_string());
+            }
+            for header in flag_values(&args, "--header") {
+                let Some((name, value)) = header.split_once(':') else {
+                    eprintln!("Invalid header '{}'. Use 'Name: value'.", header);
+                    std::process::exit(2);
+                };
+                config.crawl.headers.insert(name.trim().to_string(), value.trim().to_string());
+            }
+            if let Some(languages) = flag_value(&args, "--languages") {
+                config.crawl.languages = languages.split(',').map(|l| l.trim().to_string()).collect();
+            }
+            if let Some(sources) = flag_value(&args, "--link-sources") {
+                match sources.split(',').map(|s| s.trim().parse()).collect() {
+                    Ok(sources) => config.crawl.link_sources = sources,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+            let prefixes = flag_values(&args, "--path-prefix");
+            if !prefixes.is_empty() {
+                config.crawl.path_prefix_filter = prefixes.iter().map(|p| p.to_string()).collect();
+            }
+            if let Some(keywords) = flag_value(&args, "--focus") {
+                config.crawl.focus_keywords = keywords.split(',').map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect();
+            }
+            if let Some(min_score) = parse_flag(&args, "--focus-min-score") {
+                config.crawl.focus_min_score = min_score;
+            }
+            if args.iter().any(|a| a == "--insecure") {
+                config.crawl.danger_accept_invalid_certs = true;
+            }
+            if let Some(path) = flag_value(&args, "--block-domains") {
+                config.crawl.blocked_domains_file = Some(path.to_string());
+            }
+            if let Some(path) = flag_value(&args, "--block-terms") {
+                config.crawl.blocked_terms_file = Some(path.to_string());
+            }
+            if args.iter().any(|a| a == "--no-env-proxy") {
+                config.crawl.respect_env_proxy = false;
+            }
+            if args.iter().any(|a| a == "--record-redirects") {
+                config.crawl.record_redirects = true;
+            }
+            if let Some(languages) = flag_value(&args, "--only-lang") {
+                config.crawl.only_languages = languages.split(',').map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect();
+            }
+            if args.iter().any(|a| a == "--skip-unknown-lang") {
+                config.crawl.keep_unknown_language = false;
+            }
+            if let Some(confidence) = parse_flag(&args, "--language-confidence") {
+                config.crawl.language_confidence = confidence;
+            }
+            if let Some(delay) = parse_flag(&args, "--politeness-delay-ms") {
+                config.crawl.politeness_delay_ms = delay;
+            }
+            if let Some(rate) = parse_flag(&args, "--max-rps") {
+                config.crawl.max_requests_per_second = Some(rate);
+            }
+            if let Some(raw) = flag_value(&args, "--max-duration") {
+                match parse_duration(raw) {
+                    Ok(duration) => config.crawl.max_duration = Some(duration),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+            if let Some(raw) = flag_value(&args, "--timeout") {
+                match parse_duration(raw) {
+                    Ok(timeout) => config.crawl.request_timeout = Some(timeout),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+            if let Some(min_words) = parse_flag(&args, "--min-words") {
+                config.min_words = min_words;
+            }
+            if let Some(stopwords) = load_stopwords(&args) {
+                config.stopwords = stopwords;
+            }
+            config.export_dot = flag_value(&args, "--export-dot").map(str::to_string);
+            config.max_pages_per_domain = parse_flag(&args, "--max-per-domain");
+            if let Some(mb) = parse_flag(&args, "--writer-memory-mb") {
+                config.writer_memory_mb = mb;
+            }
+            if let Some(n) = parse_flag(&args, "--commit-every") {
+                config.commit_every = n;
+            }
+            config.dry_run = args.iter().any(|a| a == "--dry-run");
+            config.prune_to_crawled = args.iter().any(|a| a == "--prune-graph");
+            config.index_partial = args.iter().any(|a| a == "--index-partial");
+            config.pagerank = !args.iter().any(|a| a == "--no-pagerank");
+            if let Some(damping) = parse_flag(&args, "--damping") {
+                config.pagerank_config.damping = damping;
+            }
+            if let Some(iterations) = parse_flag(&args, "--pagerank-iterations") {
+                config.pagerank_config.max_iterations = iterations;
+            }
+            if let Some(weight) = parse_flag(&args, "--internal-link-weight") {
+                config.pagerank_config.internal_link_weight = weight;
+            }
+            config.http_cache = flag_value(&args, "--http-cache").map(str::to_string);
+            config.dump = flag_value(&args, "--dump").map(str::to_string);
+            config.append_seeds_from_index = args.iter().any(|a| a == "--append-seeds-from-index");
+            config.show_progress = !args.iter().any(|a| a == "--quiet");
+            quit_on_interrupt_outside_crawls();
+            match indexer::run_indexer(INDEX_PATH, &config).await {
+                // The dry-run report is the command's output, not a log line
+                Ok(Some(stats)) => {
+                    let _ = writeln!(std::io::stdout().lock(), "{}", stats);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("{}.", e);
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
+            }
+        }
+        "index-files" => {
+            let Some(pattern) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: cargo run -- index-files \"docs/**/*.md\" [--index DIR]");
+                return;
+            };
+            let mut config = indexer::IndexerConfig::default();
+            if let Some(min_words) = parse_flag(&args, "--min-words") {
+                config.min_words = min_words;
+            }
+            if let Some(stopwords) = load_stopwords(&args) {
+                config.stopwords = stopwords;
+            }
+            config.show_progress = !args.iter().any(|a| a == "--quiet");
+            let index_path = flag_value(&args, "--index").unwrap_or(INDEX_PATH);
+            indexer::files::run_file_indexer(index_path, pattern, &config)
+        }
+        "index-dump" => {
+            let Some(dump) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: cargo run -- index-dump pages.ndjson [--index DIR]");
+                return;
+            };
+            let mut config = indexer::IndexerConfig::default();
+            if let Some(min_words) = parse_flag(&args, "--min-words") {
+                config.min_words = min_words;
+            }
+            if let Some(stopwords) = load_stopwords(&args) {
+                config.stopwords = stopwords;
+            }
+            config.pagerank = !args.iter().any(|a| a == "--no-pagerank");
+            config.show_progress = !args.iter().any(|a| a == "--quiet");
+            let index_path = flag_value(&args, "--index").unwrap_or(INDEX_PATH);
+            indexer::run_dump_indexer(index_path, dump, &config)
+        }
+        "du" => indexer::maintenance::run_disk_usage(flag_value(&args, "--index").unwrap_or(INDEX_PATH)),
+        "compact" => indexer::maintenance::run_compact(flag_value(&args, "--index").unwrap_or(INDEX_PATH)),
+        "report" => {
+            let Some(log) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: cargo run -- report queries.ndjson [--limit N]");
+                return;
+            };
+            let limit = parse_flag(&args, "--limit").unwrap_or(searcher::querylog::DEFAULT_REPORT_LIMIT);
+            searcher::querylog::run_report(log, limit)
+        }
+        "search" => {
+            let config = search_config(&args);
+            if let Some(query) = flag_value(&args, "--query") {
+                let index_path = flag_value(&args, "--index").unwrap_or(INDEX_PATH);
+                let limit = parse_flag(&args, "--limit").unwrap_or(searcher::DEFAULT_LIMIT);
+                let fields = flag_value(&args, "--fields").unwrap_or(searcher::projection::DEFAULT_FIELDS);
+                if let Err(e) = searcher::projection::run_json_query(index_path, query, limit, fields, &config) {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+                return;
+            }
+            match flag_values(&args, "--index").as_slice() {
+                [] => searcher::run_searcher(INDEX_PATH, &config),
+                [path] => searcher::run_searcher(path, &config),
+                paths => searcher::multi::run_multi_searcher(paths, &config),
+            }
+        }
+        "suggest" => {
+            let Some(prefix) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: cargo run -- suggest PREFIX [--limit N] [--index DIR]");
+                return;
+            };
+            let limit = parse_flag(&args, "--limit").unwrap_or(10);
+            let index_path = flag_value(&args, "--index").unwrap_or(INDEX_PATH);
+            match searcher::Searcher::open(index_path, &search_config(&args)) {
+                Ok(engine) => {
+                    for term in engine.suggest(prefix, limit) {
+                        println!("{}", term);
+                    }
+                }
+                Err(e) => eprintln!("Error: Failed to open index directory '{}'. {}", index_path, e),
+            }
+        }
+        "scrape" => {
+            let Some(url) = args.get(2).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: cargo run -- scrape URL [--format summary|json|text|html]");
+                return;
+            };
+            let format = parse_flag(&args, "--format").unwrap_or(ScrapeFormat::Summary);
+            let scraper = Scraper::new();
+            let output = if format == ScrapeFormat::Html {
+                scraper.fetch_html(url).await
+            } else {
+                scraper.scrape(url).await.map(|page| format.render(&page))
+            };
+            match output {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("Failed to scrape '{}': {}", url, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        "export" => {
+            let format = parse_flag(&args, "--format").unwrap_or(indexer::export::ExportFormat::JsonLines);
+            let output = flag_value(&args, "--output");
+            let index_path = flag_value(&args, "--index").unwrap_or(INDEX_PATH);
+            match indexer::export::export_index(index_path, format, output) {
+                Ok(count) => eprintln!("Exported {} documents.", count),
+                Err(e) => eprintln!("Export failed: {}", e),
+            }
+        }
         _ => print_usage(),
     }
 }
 
+/// What `scrape` prints. Only the output goes to stdout, so it can be piped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScrapeFormat {
+    /// Title, language, favicon, link count and the start of the text as JSON.
+    Summary,
+    /// The whole scrape result as JSON.
+    Json,
+    /// Just the cleaned body text.
+    Text,
+    /// The fetched HTML, unparsed.
+    Html,
+}
+
+impl FromStr for ScrapeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "summary" => Ok(Self::Summary),
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            "html" => Ok(Self::Html),
+            other => Err(format!("Unknown scrape format '{}'. Use 'summary', 'json', 'text' or 'html'.", other)),
+        }
+    }
+}
+
+impl ScrapeFormat {
+    /// `page` in this format. Not used for [`Html`](Self::Html), which needs the raw body.
+    fn render(self, page: &ScrapeResult) -> String {
+        match self {
+            ScrapeFormat::Summary => {
+                let preview = serde_json::json!({
+                    "url": page.url,
+                    "title": page.title,
+                    "language": page.language,
+                    "favicon": page.favicon,
+                    "links": page.links.len(),
+                    "body": page.body_text.chars().take(SCRAPE_PREVIEW_CHARS).collect::<String>(),
+                });
+                serde_json::to_string_pretty(&preview).expect("JSON values always serialize")
+            }
+            ScrapeFormat::Json => serde_json::to_string_pretty(page).expect("scrape results always serialize"),
+            ScrapeFormat::Text => page.body_text.clone(),
+            ScrapeFormat::Html => unreachable!("HTML is printed as fetched, not rendered from a scrape result"),
+        }
+    }
+}
+
+/// Keeps Ctrl-C quitting while no crawl handles it, e.g. while the pages are indexed: a crawl
+/// leaves tokio's handler installed, which replaces the default.
+fn quit_on_interrupt_outside_crawls() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if !crawler::handles_interrupts() {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        }
+    });
+}
+
+/// Sends crawler and indexer logs to stderr. `RUST_LOG` (e.g. `RUST_LOG=search_enginge=debug`)
+/// takes precedence; otherwise `--quiet` keeps only warnings and `--verbose` adds per-page details.
+fn init_logging(args: &[String]) {
+    let default_level = if args.iter().any(|a| a == "--quiet") {
+        "warn"
+    } else if args.iter().any(|a| a == "--verbose") {
+        // Dependencies (hyper, reqwest, ...) stay at info, they're very chatty at debug
+        "info,search_enginge=debug"
+    } else {
+        "info"
+    };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_level));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Returns the value following `flag`, e.g. `--min-words 50`.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+/// Returns the values of every occurrence of a repeatable `flag`.
+fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    args.windows(2).filter(|pair| pair[0] == flag).map(|pair| pair[1].as_str()).collect()
+}
+
+/// Parses the value of `flag`, exiting with an error message if it's malformed.
+fn parse_flag<T: FromStr>(args: &[String], flag: &str) -> Option<T> {
+    let raw = flag_value(args, flag)?;
+    match raw.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            eprintln!("Invalid value '{}' for {}", raw, flag);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Seeds from `--seed-file F` followed by every `--seed URL`, or `None` if neither is
+/// given. Exits if the seed file can't be read.
+fn seeds_from_args(args: &[String]) -> Option<Vec<String>> {
+    let inline = flag_values(args, "--seed");
+    let file = flag_value(args, "--seed-file");
+    if inline.is_empty() && file.is_none() {
+        return None;
+    }
+    let mut seeds = match file {
+        Some(path) => read_seed_file(Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("Failed to read seed file '{}': {}", path, e);
+            std::process::exit(2);
+        }),
+        None => Vec::new(),
+    };
+    for seed in inline {
+        if !seeds.iter().any(|s| s == seed) {
+            seeds.push(seed.to_string());
+        }
+    }
+    Some(seeds)
+}
+
+/// Reads the crawl settings from `--config FILE`, or from `crawl_config.toml` if present.
+/// Exits if the file can't be read or parsed.
+fn load_crawl_config(args: &[String]) -> CrawlConfig {
+    let path = match flag_value(args, "--config") {
+        Some(path) => path,
+        None if Path::new(DEFAULT_CONFIG_FILE).exists() => DEFAULT_CONFIG_FILE,
+        None => return CrawlConfig::default(),
+    };
+    match CrawlConfig::from_file(Path::new(path)) {
+        Ok(config) => {
+            info!("Loaded crawl settings from '{}'.", path);
+            config
+        }
+        Err(e) => {
+            eprintln!("Failed to load crawl config '{}': {}", path, e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Builds the searcher settings shared by `search` and `suggest`.
+fn search_config(args: &[String]) -> searcher::SearchConfig {
+    let mut config = searcher::SearchConfig::default();
+    if let Some(stopwords) = load_stopwords(args) {
+        config.stopwords = stopwords;
+    }
+    if let Some(path) = flag_value(args, "--synonyms") {
+        match searcher::synonyms::SynonymMap::from_file(Path::new(path)) {
+            Ok(synonyms) => config.synonyms = synonyms,
+            Err(e) => {
+                eprintln!("Failed to read synonym file '{}': {}", path, e);
+                std::process::exit(2);
+            }
+        }
+    }
+    config.warm = args.iter().any(|a| a == "--warm");
+    if let Some(k1) = parse_flag(args, "--bm25-k1") {
+        config.bm25.k1 = k1;
+    }
+    if let Some(b) = parse_flag(args, "--bm25-b") {
+        config.bm25.b = b;
+    }
+    if let Err(e) = config.bm25.validate() {
+        eprintln!("Invalid BM25 parameters: {}", e);
+        std::process::exit(2);
+    }
+    if let Some(boost) = parse_flag(args, "--title-boost") {
+        config.boosts.title = boost;
+    }
+    if let Some(boost) = parse_flag(args, "--body-boost") {
+        config.boosts.body = boost;
+    }
+    if let Some(boost) = parse_flag(args, "--anchor-boost") {
+        config.boosts.anchor_text = boost;
+    }
+    if let Err(e) = config.boosts.validate() {
+        eprintln!("Invalid field boosts: {}", e);
+        std::process::exit(2);
+    }
+    config.max_per_domain = parse_flag::<usize>(args, "--per-domain").filter(|&max| max > 0);
+    config.normalize_by_language = args.iter().any(|a| a == "--normalize-languages");
+    config.query_log = flag_value(args, "--query-log").map(str::to_string);
+    config
+}
+
+/// Loads the custom list given with `--stopwords FILE`, exiting if the file can't be read.
+fn load_stopwords(args: &[String]) -> Option<Stopwords> {
+    let path = flag_value(args, "--stopwords")?;
+    match Stopwords::from_file(Path::new(path)) {
+        Ok(stopwords) => Some(stopwords),
+        Err(e) => {
+            eprintln!("Failed to read stopword file '{}': {}", path, e);
+            std::process::exit(2);
+        }
+    }
+}
+
 /// Prints the help message for the user.
 fn print_usage() {
     println!("--- Mini Search Engine ---");
-    println!("Usage: cargo run -- [COMMAND]");
+    println!("Usage: cargo run -- [COMMAND] [OPTIONS]");
     println!("\nCommands:");
     println!("  index     Crawl the web and build the search index.");
+    println!("  index-files  Index local .txt and Markdown files instead: index-files \"docs/**/*.md\" [--index DIR].");
+    println!("               --index DIR writes to DIR instead of {}; --min-words and --stopwords apply.", INDEX_PATH);
+    println!("  index-dump   Rebuild the index from pages written with --dump: index-dump pages.ndjson [--index DIR].");
+    println!("               --min-words, --stopwords and --no-pagerank apply.");
     println!("  search    Start the interactive search prompt (default).");
+    println!("  suggest   Print indexed terms starting with a prefix: suggest PREFIX [--limit N] [--index DIR].");
+    println!("  du        Print the size, segment count and deleted documents of the index [--index DIR].");
+    println!("  compact   Merge the index into one segment, reclaiming the space of deleted documents [--index DIR].");
+    println!("  report    Show the most frequent and the zero-result queries of a --query-log file:");
+    println!("            report queries.ndjson [--limit N] (default: {} per list).", searcher::querylog::DEFAULT_REPORT_LIMIT);
+    println!("  scrape    Fetch one URL and print its title, language, favicon, link count and text as JSON.");
+    println!("            --format json prints the whole result, text only the body text, html the fetched page.");
+    println!("  export    Dump url, title, language, pagerank, crawled_at and favicon of every document [--index DIR].");
+    println!("\nIndex options:");
+    println!("  --config F      Read crawl settings from TOML file F (default: {} if present).", DEFAULT_CONFIG_FILE);
+    println!("                  Keys: seeds, limit, concurrency, allowed_domains, denied_paths,");
+    println!("                  path_prefix_filter, user_agent, politeness_delay_ms, max_requests_per_second,");
+    println!("                  max_duration, request_timeout, languages, language_confidence, only_languages,");
+    println!("                  keep_unknown_language, link_sources, focus_keywords, focus_min_score,");
+    println!("                  danger_accept_invalid_certs, respect_env_proxy, record_redirects,");
+    println!("                  blocked_domains_file, blocked_terms_file, [headers]. The flags below");
+    println!("                  override the file.");
+    println!("  --seed-file F   Start from the URLs in file F (one per line, # comments) instead of the configured seeds.");
+    println!("  --seed URL      Also start from URL. Repeatable, combines with --seed-file.");
+    println!("  --limit N       Crawl at most N pages.");
+    println!("  --concurrency N Fetch up to N pages at once.");
+    println!("  --user-agent S  Send S as the User-Agent header.");
+    println!("  --header H      Send header H ('Name: value') with every request. Repeatable.");
+    println!("  --languages L   Only detect these comma-separated ISO 639-3 languages (e.g. eng,deu).");
+    println!("  --language-confidence X  Label pages 'unknown' below detection confidence X, 0-1 (default: 0.5).");
+    println!("  --only-lang L   Only index pages in the comma-separated languages L (e.g. en,de), still");
+    println!("                  following links from the others. Pages of unknown language are kept.");
+    println!("  --skip-unknown-lang  With --only-lang, also skip pages whose language can't be detected.");
+    println!("  --link-sources S  Also follow links from S besides <a href>: comma-separated area, pagination");
+    println!("                  (<link rel=next/prev>) and data-href.");
+    println!("  --path-prefix P Only follow links starting with URL prefix P (e.g. https://docs.rs/tokio/). Repeatable.");
+    println!("  --focus K       Focused crawl: follow links from pages about the comma-separated keywords K first");
+    println!("                  and not at all from off-topic pages.");
+    println!("  --focus-min-score X  Topic score (0-1) below which a page counts as off-topic (default: {}).",
+        search_enginge::crawler::focus::DEFAULT_MIN_TOPIC_SCORE);
+    println!("  --insecure      Accept invalid TLS certificates (self-signed test or intranet servers only).");
+    println!("  --block-domains F  Never fetch or follow links to the domains in file F (one per line or");
+    println!("                  hosts-file format), nor their subdomains.");
+    println!("  --block-terms F Drop pages containing a word or phrase of file F (one per line), without");
+    println!("                  following their links.");
+    println!("  --no-env-proxy  Connect directly, ignoring HTTP_PROXY, HTTPS_PROXY and NO_PROXY.");
+    println!("  --record-redirects  Keep each page's redirect chain, searchable with redirects:>0.");
+    println!("  --politeness-delay-ms N  Wait N ms between requests to the same host.");
+    println!("  --max-rps X     Send at most X requests per second in total, over all hosts.");
+    println!("  --max-duration D  Stop crawling after D (e.g. 90s, 10m, 2h) and index what was collected.");
+    println!("  --timeout D     Give up on a page after D (e.g. 20s), from connecting until parsed (default: 10s).");
+    println!("  --min-words N   Skip pages with fewer than N words (default: {}).", indexer::DEFAULT_MIN_WORDS);
+    println!("  --export-dot F  Write the crawled link graph to GraphViz file F.");
+    println!("  --max-per-domain N  Crawl at most N pages from any single site (www. counts as the same site).");
+    println!("  --writer-memory-mb N  Index writer heap in MB, {}-{} (default: {}).",
+        indexer::MIN_WRITER_MEMORY_MB, indexer::MAX_WRITER_MEMORY_MB, indexer::DEFAULT_WRITER_MEMORY_MB);
+    println!("                  Lower it on small machines, raise it for large crawls.");
+    println!("  --commit-every N  Index pages in batches of N, committing after each (default: {}).", indexer::DEFAULT_COMMIT_EVERY);
+    println!("  --dry-run       Crawl and print statistics without writing the index.");
+    println!("  --prune-graph   Rank only crawled pages, ignoring links to pages outside the crawl.");
+    println!("  --no-pagerank   Skip PageRank (all pages rank 0) for a faster, BM25-only index.");
+    println!("  --damping X     PageRank damping factor, between 0 and 1 (default: {}).", indexer::algorithms::pagerank::DEFAULT_DAMPING_FACTOR);
+    println!("  --pagerank-iterations N  Stop PageRank after at most N iterations (default: {}).", indexer::algorithms::pagerank::DEFAULT_MAX_ITERATIONS);
+    println!("  --internal-link-weight X  Weight of same-site links in PageRank, 0 to 1 (default: {}).", indexer::algorithms::pagerank::DEFAULT_INTERNAL_LINK_WEIGHT);
+    println!("  --index-partial Index the meta description of paywalled pages, ranked below full pages.");
+    println!("  --http-cache F  Revalidate pages cached in F with conditional GETs and update F.");
+    println!("  --append-seeds-from-index  Also recrawl every page of the existing index to refresh it.");
+    println!("  --dump F        Write each page to NDJSON file F as it's crawled and index from F, so large");
+    println!("                  crawls don't hold every page in memory. Reindex later with index-dump F.");
+    println!("\nSearch options:");
+    println!("  --index DIR     Search the index in DIR instead of {}. Repeat to search several", INDEX_PATH);
+    println!("                  indexes at once, with results merged by relevance.");
+    println!("  --bm25-k1 X     Term-frequency saturation (default: {}). Lower values reward repeated terms less.", searcher::bm25::DEFAULT_K1);
+    println!("  --bm25-b X      Length normalization, 0-1 (default: {}). Lower it for short technical pages.", searcher::bm25::DEFAULT_B);
+    println!("  --title-boost X Weight of title matches (default: {}).", searcher::DEFAULT_TITLE_BOOST);
+    println!("  --body-boost X  Weight of body matches (default: {}).", searcher::DEFAULT_BODY_BOOST);
+    println!("  --anchor-boost X  Weight of inbound link text matches (default: {}).", searcher::DEFAULT_ANCHOR_BOOST);
+    println!("  --query Q       Run query Q once and print the results as JSON lines instead of prompting.");
+    println!("  --fields F      Comma-separated fields of each --query result (default: {}): score or", searcher::projection::DEFAULT_FIELDS);
+    println!("                  any stored field, e.g. language, pagerank, crawled_at, word_count.");
+    println!("  --limit N       Print at most N --query results (default: {}).", searcher::DEFAULT_LIMIT);
+    println!("  --per-domain K  Show at most K results per domain; 'show all from DOMAIN' lists the rest.");
+    println!("  --normalize-languages  Rank each language's matches on its own scale before merging them,");
+    println!("                  so pages in a minority language aren't outscored by corpus statistics.");
+    println!("  --warm          Preload the index files into the page cache before the first query.");
+    println!("  --synonyms F    Expand query words using the 'word -> synonym, ...' rules in file F.");
+    println!("  --query-log F   Append each query with its result count and latency to NDJSON file F.");
+    println!("                  Off by default; summarize the file with 'report F'.");
+    println!("\nExport options:");
+    println!("  --format F      'jsonl' (default) or 'csv'.");
+    println!("  --output F      Write to file F instead of stdout.");
+    println!("  --index DIR     Export the index in DIR instead of ./search_index.");
+    println!("\nShared options:");
+    println!("  --quiet         Only log warnings and errors, and hide progress bars.");
+    println!("  --verbose       Also log debug details such as every fetched URL.");
+    println!("                  RUST_LOG overrides both, e.g. RUST_LOG=search_enginge=debug.");
+    println!("  --stopwords F   Use the words in file F (one per line) instead of the English stopwords.");
+    println!("                  Pass the same file to both 'index' and 'search'.");
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// A progress bar for `len` items of `unit` (e.g. "pages") with throughput and ETA.
+///
+/// Hidden when `enabled` is false; indicatif also hides it by itself when stderr isn't a
+/// terminal, so redirected or unattended runs get plain logs only.
+pub fn bar(len: u64, unit: &str, enabled: bool) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+    let template = format!("[{{elapsed_precise}}] {{bar:40}} {{pos}}/{{len}} {} ({{per_sec}}, ETA {{eta}})", unit);
+    let style = ProgressStyle::with_template(&template).unwrap_or_else(|_| ProgressStyle::default_bar());
+    ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::stderr()).with_style(style)
+}
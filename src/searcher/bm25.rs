@@ -0,0 +1,131 @@
+use tantivy::collector::TopDocs;
+use tantivy::postings::{Postings, SegmentPostings};
+use tantivy::query::{Bm25StatisticsProvider, Query};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocAddress, DocId, DocSet, Score, SegmentReader, Term};
+
+use super::matched::QueryWord;
+
+/// tantivy's built-in term-frequency saturation.
+pub const DEFAULT_K1: f32 = 1.2;
+/// tantivy's built-in length normalization.
+pub const DEFAULT_B: f32 = 0.75;
+
+/// BM25 ranking parameters.
+///
+/// `k1` controls how quickly repeated occurrences of a term stop adding to the score:
+/// near 0 a single occurrence counts as much as many, larger values keep rewarding
+/// repetition. `b` controls how much long documents are penalized: 0 ignores length,
+/// 1 fully normalizes by it. Corpora of short technical pages often rank better with a
+/// lower `b`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25Params {
+    pub k1: f32,
+    pub b: f32,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: DEFAULT_K1, b: DEFAULT_B }
+    }
+}
+
+impl Bm25Params {
+    /// `k1` must be non-negative and `b` within `0..=1`.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.k1.is_finite() && self.k1 >= 0.0) {
+            return Err(format!("k1 must be a non-negative number, got {}.", self.k1));
+        }
+        if !(0.0..=1.0).contains(&self.b) {
+            return Err(format!("b must be between 0 and 1, got {}.", self.b));
+        }
+        Ok(())
+    }
+
+    /// Whether these are tantivy's own parameters, so its scoring can be used as is.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// One query token in one field, with the index-wide statistics BM25 needs.
+#[derive(Clone)]
+struct TermStats {
+    term: Term,
+    field: Field,
+    idf: Score,
+    average_fieldnorm: Score,
+    boost: Score,
+}
+
+/// Top `limit` matches of `query`, ranked by BM25 with `params` over the analyzed query
+/// `words` in `fields`, each field's score multiplied by its boost.
+///
+/// tantivy hardcodes k1 and b in its scorer, so tuned parameters are applied by
+/// rescoring each match. The query still decides which documents match; scoring treats
+/// it as a bag of words, so phrase and boost structure only affect matching. Partial
+/// pages are down-weighted like in [`Searcher::top_by_relevance`](super::Searcher::top_by_relevance).
+pub fn top_docs(
+    searcher: &tantivy::Searcher,
+    query: &dyn Query,
+    fields: &[(Field, Score)],
+    words: &[QueryWord],
+    params: Bm25Params,
+    limit: usize,
+) -> tantivy::Result<Vec<(Score, DocAddress)>> {
+    let total_docs = searcher.total_num_docs()?.max(1) as Score;
+    let mut stats = Vec::new();
+    for &(field, boost) in fields {
+        let average_fieldnorm = searcher.total_num_tokens(field)? as Score / total_docs;
+        for word in words {
+            let term = Term::from_field_text(field, &word.token);
+            let doc_freq = searcher.doc_freq(&term)? as Score;
+            let idf = (1.0 + (total_docs - doc_freq + 0.5) / (doc_freq + 0.5)).ln();
+            stats.push(TermStats { term, field, idf, average_fieldnorm, boost });
+        }
+    }
+
+    let collector = TopDocs::with_limit(limit).tweak_score(move |segment_reader: &SegmentReader| {
+        let mut scorers = segment_scorers(segment_reader, &stats);
+        let weight = super::partial_weights(segment_reader);
+        move |doc: DocId, _original: Score| {
+            scorers.iter_mut().map(|scorer| scorer.score(doc, params)).sum::<Score>() * weight(doc)
+        }
+    });
+    searcher.search(query, &collector)
+}
+
+/// Per-segment postings and field lengths for one [`TermStats`].
+struct TermScorer {
+    stats: TermStats,
+    postings: SegmentPostings,
+    fieldnorms: Option<tantivy::fieldnorm::FieldNormReader>,
+}
+
+impl TermScorer {
+    /// Docs are collected in increasing order within a segment, so the postings only
+    /// ever need to seek forward. A postings list already past `doc` (or exhausted,
+    /// which is `TERMINATED`) means the term isn't in it.
+    fn score(&mut self, doc: DocId, params: Bm25Params) -> Score {
+        if self.postings.doc() > doc || self.postings.seek(doc) != doc {
+            return 0.0;
+        }
+        let term_freq = self.postings.term_freq() as Score;
+        let fieldnorm = self.fieldnorms.as_ref().map_or(self.stats.average_fieldnorm, |r| r.fieldnorm(doc) as Score);
+        let length_ratio = if self.stats.average_fieldnorm > 0.0 { fieldnorm / self.stats.average_fieldnorm } else { 1.0 };
+        let norm = params.k1 * (1.0 - params.b + params.b * length_ratio);
+        self.stats.boost * self.stats.idf * term_freq * (params.k1 + 1.0) / (term_freq + norm)
+    }
+}
+
+fn segment_scorers(segment_reader: &SegmentReader, stats: &[TermStats]) -> Vec<TermScorer> {
+    stats
+        .iter()
+        .filter_map(|stats| {
+            let inverted_index = segment_reader.inverted_index(stats.field).ok()?;
+            let postings = inverted_index.read_postings(&stats.term, IndexRecordOption::WithFreqs).ok().flatten()?;
+            let fieldnorms = segment_reader.get_fieldnorms_reader(stats.field).ok();
+            Some(TermScorer { stats: stats.clone(), postings, fieldnorms })
+        })
+        .collect()
+}
@@ -0,0 +1,71 @@
+use tantivy::query::{BooleanQuery, Occur, Query, RegexQuery};
+use tantivy::schema::Field;
+
+use super::query::regex_escape;
+use crate::crawler::datascraper::{site_host, url_key};
+
+/// Candidates fetched per shown result when results are capped per domain, so capping
+/// still leaves enough other pages to fill the list.
+pub const CANDIDATES_PER_RESULT: usize = 5;
+/// Candidates fetched per shown result when only duplicates are dropped, which are rarer
+/// than several pages of one domain.
+pub const DEDUP_CANDIDATES_PER_RESULT: usize = 2;
+
+/// The first of `hits` for each page, by [`url_key`], so one page indexed under several
+/// URLs (e.g. by crawls before and after a site moved to HTTPS) is listed once, at its
+/// best rank. Keeps at most `limit` hits and their order.
+pub fn dedup_by_url<T>(hits: Vec<T>, limit: usize, url: impl Fn(&T) -> String) -> Vec<T> {
+    let mut seen = std::collections::HashSet::new();
+    hits.into_iter().filter(|hit| seen.insert(url_key(&url(hit)))).take(limit).collect()
+}
+
+/// Hits left after [`cap_per_domain`], and how many were held back for each domain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capped<T> {
+    pub hits: Vec<T>,
+    /// Domains that had more hits than allowed, in the order they first appeared, with the
+    /// number of hidden hits among the candidates.
+    pub hidden: Vec<(String, usize)>,
+}
+
+/// Keeps at most `max_per_domain` of `hits` from each domain and at most `limit` in
+/// total, without changing their order. `url` gives a hit's URL; hits whose URL has no
+/// host are never capped.
+pub fn cap_per_domain<T>(hits: Vec<T>, max_per_domain: usize, limit: usize, url: impl Fn(&T) -> String) -> Capped<T> {
+    let mut shown: Vec<(String, usize)> = Vec::new();
+    let mut hidden: Vec<(String, usize)> = Vec::new();
+    let mut kept = Vec::new();
+    for hit in hits {
+        let Some(domain) = site_host(&url(&hit)) else {
+            if kept.len() < limit {
+                kept.push(hit);
+            }
+            continue;
+        };
+        let count = match shown.iter_mut().find(|(d, _)| *d == domain) {
+            Some((_, count)) => count,
+            None => {
+                shown.push((domain.clone(), 0));
+                &mut shown.last_mut().expect("just pushed").1
+            }
+        };
+        if *count < max_per_domain && kept.len() < limit {
+            *count += 1;
+            kept.push(hit);
+        } else if *count >= max_per_domain {
+            match hidden.iter_mut().find(|(d, _)| *d == domain) {
+                Some((_, n)) => *n += 1,
+                None => hidden.push((domain, 1)),
+            }
+        }
+    }
+    Capped { hits: kept, hidden }
+}
+
+/// `query` restricted to pages on `domain` (or its `www.` variant), for `show all from DOMAIN`.
+pub fn restrict_to_domain(query: Box<dyn Query>, url_field: Field, domain: &str) -> Result<Box<dyn Query>, String> {
+    let domain = domain.trim().trim_start_matches("www.").to_ascii_lowercase();
+    let pattern = format!("(?i)https?://(www\\.)?{}(:[0-9]+)?/.*", regex_escape(&domain));
+    let regex = RegexQuery::from_pattern(&pattern, url_field).map_err(|e| format!("Invalid domain '{}': {}", domain, e))?;
+    Ok(Box::new(BooleanQuery::new(vec![(Occur::Must, query), (Occur::Must, Box::new(regex))])))
+}
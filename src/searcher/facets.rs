@@ -0,0 +1,46 @@
+use std::collections::BTreeSet;
+use tantivy::collector::Count;
+use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::Term;
+
+/// Counts how many documents matching `query` fall into each language, most common first.
+///
+/// The language field is a raw STRING field, so its term dictionary holds exactly the
+/// distinct language codes. We run one count per code, which stays cheap because an
+/// index only ever contains a handful of languages.
+pub fn language_counts(
+    searcher: &tantivy::Searcher,
+    query: &dyn Query,
+    language_field: Field,
+) -> tantivy::Result<Vec<(String, usize)>> {
+    let mut languages = BTreeSet::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(language_field)?;
+        let mut stream = inverted_index.terms().stream()?;
+        while stream.advance() {
+            if let Ok(lang) = std::str::from_utf8(stream.key()) {
+                languages.insert(lang.to_string());
+            }
+        }
+    }
+
+    let mut counts = Vec::with_capacity(languages.len());
+    for lang in languages {
+        let lang_query = TermQuery::new(
+            Term::from_field_text(language_field, &lang),
+            IndexRecordOption::Basic,
+        );
+        let filtered = BooleanQuery::new(vec![
+            (Occur::Must, query.box_clone()),
+            (Occur::Must, Box::new(lang_query)),
+        ]);
+        let count = searcher.search(&filtered, &Count)?;
+        if count > 0 {
+            counts.push((lang, count));
+        }
+    }
+
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(counts)
+}
@@ -0,0 +1,152 @@
+use std::ops::Bound;
+
+use tantivy::query::{Query, RangeQuery};
+use tantivy::schema::Schema;
+use tantivy::time::{Date, Month};
+use tantivy::Term;
+
+use super::query::split_clauses;
+use crate::indexer::schema::WebpageSchema;
+
+/// Removes range clauses such as `words:>500`, `pagerank:>0.001`,
+/// `crawled_at:[2024-01-01 TO *]`, `status:200` or `redirects:>0` from `query` and turns them into range queries on the
+/// matching fast fields. Returns the remaining query text and the filters, or an error
+/// message for a malformed bound.
+///
+/// A bound is either a comparison (`>`, `>=`, `<`, `<=`, or a plain value for equality)
+/// or a range: `[a TO b]` includes both ends, `{a TO b}` excludes them and `*` leaves a
+/// side open. `crawled_at` takes `YYYY-MM-DD` dates (UTC midnight) or Unix seconds.
+///
+/// `schema` is the schema of the opened index: older indexes may lack a filter's field.
+pub fn extract_range_filters(
+    query: &str,
+    fields: &WebpageSchema,
+    schema: &Schema,
+) -> Result<(String, Vec<Box<dyn Query>>), String> {
+    let mut rest = Vec::new();
+    let mut filters: Vec<Box<dyn Query>> = Vec::new();
+    for clause in split_clauses(query) {
+        let Some((name, expr)) = clause.split_once(':') else {
+            rest.push(clause);
+            continue;
+        };
+        let invalid = |e: String| format!("Invalid {} filter '{}': {}", name, clause, e);
+        let filter = match name {
+            "words" => {
+                require_field(schema, "word_count")?;
+                let (lower, upper) = parse_bounds(expr, parse_count).map_err(invalid)?;
+                let term = |n: i64| Term::from_field_i64(fields.word_count, n);
+                RangeQuery::new(lower.map(term), upper.map(term))
+            }
+            "pagerank" => {
+                require_field(schema, "pagerank")?;
+                let (lower, upper) = parse_bounds(expr, parse_score).map_err(invalid)?;
+                let term = |x: f64| Term::from_field_f64(fields.pagerank, x);
+                RangeQuery::new(lower.map(term), upper.map(term))
+            }
+            "crawled_at" => {
+                require_field(schema, "crawled_at")?;
+                let (lower, upper) = parse_bounds(expr, parse_timestamp).map_err(invalid)?;
+                let term = |secs: i64| Term::from_field_i64(fields.crawled_at, secs);
+                RangeQuery::new(lower.map(term), upper.map(term))
+            }
+            "status" | "redirects" => {
+                require_field(schema, name)?;
+                let field = if name == "status" { fields.status } else { fields.redirects };
+                let (lower, upper) = parse_bounds(expr, parse_integer).map_err(invalid)?;
+                let term = |n: i64| Term::from_field_i64(field, n);
+                RangeQuery::new(lower.map(term), upper.map(term))
+            }
+            _ => {
+                rest.push(clause);
+                continue;
+            }
+        };
+        filters.push(Box::new(filter));
+    }
+    Ok((rest.join(" "), filters))
+}
+
+fn require_field(schema: &Schema, name: &str) -> Result<(), String> {
+    schema
+        .get_field(name)
+        .map(|_| ())
+        .map_err(|_| format!("This index has no '{}' field. Rebuild it with `cargo run -- index`.", name))
+}
+
+/// Parses a comparison (`>N`, `>=N`, `<N`, `<=N`, `N`) or a `[a TO b]`/`{a TO b}` range
+/// into lower and upper bounds, rejecting empty ranges.
+fn parse_bounds<T: PartialOrd + Clone>(
+    expr: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<(Bound<T>, Bound<T>), String> {
+    let (lower, upper) = if let Some(range) = expr.strip_prefix(['[', '{']) {
+        let inclusive = expr.starts_with('[');
+        let closing = if inclusive { ']' } else { '}' };
+        let Some(range) = range.strip_suffix(closing) else {
+            return Err(format!("range must end with '{}'", closing));
+        };
+        let Some((from, to)) = range.split_once(" TO ") else {
+            return Err("range must look like [a TO b]".to_string());
+        };
+        let bound = |raw: &str| -> Result<Bound<T>, String> {
+            match raw.trim() {
+                "*" => Ok(Bound::Unbounded),
+                raw if inclusive => parse(raw).map(Bound::Included),
+                raw => parse(raw).map(Bound::Excluded),
+            }
+        };
+        (bound(from)?, bound(to)?)
+    } else if let Some(raw) = expr.strip_prefix(">=") {
+        (Bound::Included(parse(raw)?), Bound::Unbounded)
+    } else if let Some(raw) = expr.strip_prefix('>') {
+        (Bound::Excluded(parse(raw)?), Bound::Unbounded)
+    } else if let Some(raw) = expr.strip_prefix("<=") {
+        (Bound::Unbounded, Bound::Included(parse(raw)?))
+    } else if let Some(raw) = expr.strip_prefix('<') {
+        (Bound::Unbounded, Bound::Excluded(parse(raw)?))
+    } else {
+        let value = parse(expr)?;
+        (Bound::Included(value.clone()), Bound::Included(value))
+    };
+
+    if let (Bound::Included(lo) | Bound::Excluded(lo), Bound::Included(hi) | Bound::Excluded(hi)) = (&lower, &upper)
+        && lo > hi
+    {
+        return Err("lower bound is greater than upper bound".to_string());
+    }
+    Ok((lower, upper))
+}
+
+fn parse_count(raw: &str) -> Result<i64, String> {
+    raw.trim().parse::<u32>().map(i64::from).map_err(|_| format!("'{}' is not a valid word count", raw.trim()))
+}
+
+fn parse_integer(raw: &str) -> Result<i64, String> {
+    raw.trim().parse::<u32>().map(i64::from).map_err(|_| format!("'{}' is not a valid whole number", raw.trim()))
+}
+
+fn parse_score(raw: &str) -> Result<f64, String> {
+    match raw.trim().parse::<f64>() {
+        Ok(x) if x.is_finite() => Ok(x),
+        _ => Err(format!("'{}' is not a valid number", raw.trim())),
+    }
+}
+
+/// `YYYY-MM-DD` (midnight UTC) or Unix seconds.
+fn parse_timestamp(raw: &str) -> Result<i64, String> {
+    let raw = raw.trim();
+    if let Ok(secs) = raw.parse::<i64>() {
+        return Ok(secs);
+    }
+    let invalid = || format!("'{}' is not a YYYY-MM-DD date or Unix timestamp", raw);
+    let mut parts = raw.splitn(3, '-');
+    let (Some(y), Some(m), Some(d)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let year = y.parse::<i32>().map_err(|_| invalid())?;
+    let month = m.parse::<u8>().ok().and_then(|m| Month::try_from(m).ok()).ok_or_else(invalid)?;
+    let day = d.parse::<u8>().map_err(|_| invalid())?;
+    let date = Date::from_calendar_date(year, month, day).map_err(|_| invalid())?;
+    Ok(date.midnight().assume_utc().unix_timestamp())
+}
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use tantivy::{DocAddress, Score};
+
+/// Candidates fetched per result when scores are normalized per language, so each
+/// language's best matches are among the candidates before the lists are merged.
+pub const CANDIDATES_PER_RESULT: usize = 5;
+
+/// Rescales `hits` so the best hit of each language scores 1 and the others keep their
+/// score relative to it, then merges them back into one list, best first. `language`
+/// gives a hit's language code.
+///
+/// BM25 scores depend on corpus statistics (document frequencies, average length), so
+/// in an index that is mostly English a German page's scores come out on a different
+/// scale. Normalizing puts the best match of every language on equal footing. The cost:
+/// it also promotes the best of a language that only has weak matches, and a language
+/// with a single candidate always scores 1. Scores are only comparable within one
+/// result list afterwards.
+pub fn normalize_per_language(hits: Vec<(Score, DocAddress)>, language: impl Fn(DocAddress) -> String) -> Vec<(Score, DocAddress)> {
+    let languages: Vec<String> = hits.iter().map(|&(_, address)| language(address)).collect();
+    let mut best: HashMap<&str, Score> = HashMap::new();
+    for (&(score, _), language) in hits.iter().zip(&languages) {
+        let top = best.entry(language).or_insert(score);
+        *top = top.max(score);
+    }
+    let mut normalized: Vec<(Score, DocAddress)> = hits
+        .iter()
+        .zip(&languages)
+        .map(|(&(score, address), language)| {
+            let top = best[language.as_str()];
+            (if top > 0.0 { score / top } else { 0.0 }, address)
+        })
+        .collect();
+    normalized.sort_by(|a, b| b.0.total_cmp(&a.0));
+    normalized
+}
@@ -0,0 +1,59 @@
+use tantivy::postings::Postings;
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::tokenizer::TextAnalyzer;
+use tantivy::{DocAddress, DocSet, Term};
+
+use super::query::positive_words;
+
+/// A word the user typed and the token it becomes after analysis (lowercased, stemmed).
+#[derive(Debug, Clone)]
+pub struct QueryWord {
+    pub word: String,
+    pub token: String,
+}
+
+/// Analyzes the positive words of `query`. Stopwords produce no token and are dropped.
+pub fn query_words(analyzer: &mut TextAnalyzer, query: &str) -> Vec<QueryWord> {
+    let mut words: Vec<QueryWord> = Vec::new();
+    for word in positive_words(query) {
+        let mut stream = analyzer.token_stream(word);
+        while let Some(token) = stream.next() {
+            if words.iter().all(|w| w.token != token.text) {
+                words.push(QueryWord { word: word.to_lowercase(), token: token.text.clone() });
+            }
+        }
+    }
+    words
+}
+
+/// Splits `words` into those present in `doc` (in any of `fields`) and those missing,
+/// by probing the postings list of each token.
+pub fn matched_terms<'a>(
+    searcher: &tantivy::Searcher,
+    fields: &[Field],
+    doc: DocAddress,
+    words: &'a [QueryWord],
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    let segment_reader = searcher.segment_reader(doc.segment_ord);
+    let (mut matched, mut missing) = (Vec::new(), Vec::new());
+
+    for word in words {
+        let found = fields.iter().any(|&field| {
+            let term = Term::from_field_text(field, &word.token);
+            segment_reader
+                .inverted_index(field)
+                .ok()
+                .and_then(|inv| inv.read_postings(&term, IndexRecordOption::Basic).ok().flatten())
+                // `seek` only moves forward, so a list already past the doc doesn't contain it
+                .is_some_and(|mut postings| {
+                    postings.doc() <= doc.doc_id && postings.seek(doc.doc_id) == doc.doc_id && postings.term_freq() > 0
+                })
+        });
+        if found {
+            matched.push(word.word.as_str());
+        } else {
+            missing.push(word.word.as_str());
+        }
+    }
+    (matched, missing)
+}
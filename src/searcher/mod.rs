@@ -2,19 +2,473 @@
 
 use std::io::{self, Write};
 use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, RegexQuery};
 use tantivy::schema::*;
-use tantivy::{Index, TantivyDocument};
+use tantivy::time::OffsetDateTime;
+use tantivy::{Directory, DocAddress, DocId, Index, IndexReader, Order, ReloadPolicy, Score, SegmentReader, TantivyDocument, TantivyError};
 
 // Import schema from the indexer module
+use crate::indexer::meta::IndexMeta;
 use crate::indexer::schema::WebpageSchema;
+use crate::text::Stopwords;
+
+pub mod bm25;
+pub mod diversity;
+pub mod facets;
+pub mod filters;
+pub mod language_norm;
+pub mod matched;
+pub mod multi;
+pub mod projection;
+pub mod query;
+pub mod querylog;
+pub mod related;
+pub mod similar;
+pub mod suggest;
+pub mod synonyms;
+
+/// Below this many hits we look for spelling corrections.
+const FEW_RESULTS: usize = 3;
+/// Related terms suggested after each result list.
+const RELATED_TERMS: usize = 5;
+/// Fields an index needs to be searched and displayed at all. Everything else is
+/// optional: indexes built by earlier versions lack the fields added since.
+const REQUIRED_FIELDS: [&str; 3] = ["url", "title", "body"];
+/// Results shown per query unless changed with `limit N`.
+pub const DEFAULT_LIMIT: usize = 10;
+
+/// Relevance multiplier for paywalled pages indexed from their meta description only.
+pub const PARTIAL_WEIGHT: Score = 0.5;
+
+/// Score multiplier for each document of one segment: [`PARTIAL_WEIGHT`] for partial
+/// pages, 1 otherwise (and for every document of an index without the `partial` field).
+fn partial_weights(segment_reader: &SegmentReader) -> impl Fn(DocId) -> Score + use<> {
+    let column = segment_reader.fast_fields().bool("partial").ok();
+    move |doc| match column.as_ref().and_then(|c| c.first(doc)) {
+        Some(true) => PARTIAL_WEIGHT,
+        _ => 1.0,
+    }
+}
+
+/// Title matches count three times as much as body matches by default: a page named
+/// after the query is usually about it, one mentioning it in passing often isn't.
+pub const DEFAULT_TITLE_BOOST: Score = 3.0;
+pub const DEFAULT_BODY_BOOST: Score = 1.0;
+/// Inbound link text describes a page much like its title, but is noisier.
+pub const DEFAULT_ANCHOR_BOOST: Score = 1.0;
+
+/// Score multipliers for query matches in each searched field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldBoosts {
+    pub title: Score,
+    pub body: Score,
+    pub anchor_text: Score,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        Self { title: DEFAULT_TITLE_BOOST, body: DEFAULT_BODY_BOOST, anchor_text: DEFAULT_ANCHOR_BOOST }
+    }
+}
+
+impl FieldBoosts {
+    /// Every boost must be a positive number.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, boost) in [("title", self.title), ("body", self.body), ("anchor", self.anchor_text)] {
+            if !(boost.is_finite() && boost > 0.0) {
+                return Err(format!("{} boost must be a positive number, got {}.", name, boost));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Settings for an interactive search session.
+#[derive(Debug, Clone, Default)]
+pub struct SearchConfig {
+    /// Must match the stopwords the index was built with.
+    pub stopwords: Stopwords,
+    /// Ranking parameters for relevance-sorted results.
+    pub bm25: bm25::Bm25Params,
+    /// Weight of title, body and anchor text matches, see [`DEFAULT_TITLE_BOOST`].
+    pub boosts: FieldBoosts,
+    /// Query terms to expand before parsing. Empty by default.
+    pub synonyms: synonyms::SynonymMap,
+    /// Read the whole index once at startup so the first queries don't hit cold pages.
+    pub warm: bool,
+    /// Show at most this many results per domain, see [`diversity::cap_per_domain`].
+    pub max_per_domain: Option<usize>,
+    /// Normalize relevance scores within each language, see
+    /// [`Searcher::with_language_normalization`].
+    pub normalize_by_language: bool,
+    /// Append every search with its result count and latency to this NDJSON file, see
+    /// [`querylog::QueryLog`]. Off by default.
+    pub query_log: Option<String>,
+}
+
+impl SearchConfig {
+    /// Opens [`query_log`](Self::query_log) if one is set.
+    pub fn open_query_log(&self) -> io::Result<Option<querylog::QueryLog>> {
+        self.query_log.as_deref().map(|path| querylog::QueryLog::open(std::path::Path::new(path))).transpose()
+    }
+}
+
+/// How results are ordered in the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// BM25 relevance score (default).
+    Relevance,
+    /// Highest PageRank first.
+    PageRank,
+    /// Newest `crawled_at` first.
+    Date,
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "relevance" => Ok(Self::Relevance),
+            "pagerank" => Ok(Self::PageRank),
+            "date" => Ok(Self::Date),
+            other => Err(format!("Unknown sort order '{}'. Use 'relevance', 'pagerank' or 'date'.", other)),
+        }
+    }
+}
+
+/// Settings changed at the prompt that persist across queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub limit: usize,
+    pub sort: SortOrder,
+    /// Results shown per domain, `None` for no cap.
+    pub max_per_domain: Option<usize>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self { limit: DEFAULT_LIMIT, sort: SortOrder::Relevance, max_per_domain: None }
+    }
+}
+
+impl Session {
+    /// Applies a `limit N`, `sort ORDER` or `per-domain N|off` pseudo-command. Returns
+    /// `None` if `input` isn't one, otherwise a confirmation or error message.
+    pub fn apply_command(&mut self, input: &str) -> Option<Result<String, String>> {
+        if let Some(n) = input.strip_prefix("limit ") {
+            return Some(match n.trim().parse::<usize>() {
+                Ok(limit) if limit > 0 => {
+                    self.limit = limit;
+                    Ok(format!("Showing up to {} results.", limit))
+                }
+                _ => Err(format!("Invalid limit '{}'. Use a positive number.", n.trim())),
+            });
+        }
+        if let Some(order) = input.strip_prefix("sort ") {
+            return Some(order.trim().parse::<SortOrder>().map(|sort| {
+                self.sort = sort;
+                format!("Sorting results by {:?}.", sort)
+            }));
+        }
+        if let Some(n) = input.strip_prefix("per-domain ") {
+            return Some(match n.trim() {
+                "off" => {
+                    self.max_per_domain = None;
+                    Ok("Showing all results of a domain.".to_string())
+                }
+                n => match n.parse::<usize>() {
+                    Ok(max) if max > 0 => {
+                        self.max_per_domain = Some(max);
+                        Ok(format!("Showing up to {} results per domain.", max))
+                    }
+                    _ => Err(format!("Invalid per-domain cap '{}'. Use a positive number or 'off'.", n)),
+                },
+            });
+        }
+        None
+    }
+}
+
+/// Formats a Unix timestamp (seconds) as `YYYY-MM-DD HH:MM UTC`.
+pub fn format_timestamp(secs: i64) -> String {
+    match OffsetDateTime::from_unix_timestamp(secs) {
+        Ok(dt) => format!(
+            "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+            dt.year(), dt.month() as u8, dt.day(), dt.hour(), dt.minute()
+        ),
+        Err(_) => "[Unknown]".to_string(),
+    }
+}
+
+/// A loaded index ready to be queried. This is the programmatic entry point;
+/// [`run_searcher`] wraps it in an interactive prompt.
+pub struct Searcher {
+    index: Index,
+    reader: IndexReader,
+    fields: WebpageSchema,
+    search_fields: Vec<Field>,
+    boosts: Vec<(Field, Score)>,
+    query_parser: QueryParser,
+    bm25: bm25::Bm25Params,
+    synonyms: synonyms::SynonymMap,
+    meta: Option<IndexMeta>,
+    normalize_by_language: bool,
+}
+
+impl Searcher {
+    /// Opens the index in `index_path` along with its [`IndexMeta`], if it has one.
+    pub fn open(index_path: &str, config: &SearchConfig) -> tantivy::Result<Self> {
+        let mut searcher = Self::from_index(Index::open_in_dir(index_path)?, config)?;
+        searcher.meta = IndexMeta::read(std::path::Path::new(index_path))?;
+        Ok(searcher)
+    }
+
+    /// Wraps an already opened index, e.g. an in-memory one from
+    /// [`index_pages`](crate::indexer::index_pages).
+    ///
+    /// Fails if the index's schema isn't the current one or an older one it extends
+    /// (fields only ever get appended), or lacks the url, title or body field.
+    pub fn from_index(index: Index, config: &SearchConfig) -> tantivy::Result<Self> {
+        check_schema(&index.schema()).map_err(TantivyError::SchemaError)?;
+
+        // CRITICAL: We must register the "en_stem" tokenizer logic in the searcher too,
+        // otherwise it won't know how to parse the query words.
+        WebpageSchema::register_tokenizer(&index, &config.stopwords);
+
+        // Build fields helper to access field constants safely
+        let (_schema, fields) = WebpageSchema::build();
+
+        // Commits write their segments first and then atomically replace `meta.json`; the
+        // reader only switches over once that has happened, and every `searcher()` is a
+        // snapshot of whole segments. So an indexer committing batches while we search is
+        // picked up within milliseconds, and no query sees a half-written segment.
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::OnCommitWithDelay).try_into()?;
+
+        // We search in Title, Body and inbound anchor text (indexes built before anchors lack it)
+        let mut boosts = vec![(fields.title, config.boosts.title), (fields.body, config.boosts.body)];
+        if index.schema().get_field("anchor_text").is_ok() {
+            boosts.push((fields.anchor_text, config.boosts.anchor_text));
+        }
+        let search_fields: Vec<Field> = boosts.iter().map(|&(field, _)| field).collect();
+        let mut query_parser = QueryParser::for_index(&index, search_fields.clone());
+        for &(field, boost) in &boosts {
+            query_parser.set_field_boost(field, boost);
+        }
+
+        Ok(Self {
+            index,
+            reader,
+            fields,
+            search_fields,
+            boosts,
+            query_parser,
+            bm25: config.bm25,
+            synonyms: Default::default(),
+            meta: None,
+            normalize_by_language: false,
+        }
+        .with_synonyms(config.synonyms.clone())
+        .with_language_normalization(config.normalize_by_language))
+    }
+
+    /// Expands query terms with `synonyms` (OR-ed with the original term) before parsing.
+    pub fn with_synonyms(mut self, synonyms: synonyms::SynonymMap) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// Scales relevance scores per language before merging the languages, for indexes
+    /// mixing them, instead of comparing raw BM25 scores. Off by default; see
+    /// [`language_norm::normalize_per_language`] for the tradeoffs. Indexes without a
+    /// language field are unaffected.
+    pub fn with_language_normalization(mut self, enabled: bool) -> Self {
+        self.normalize_by_language = enabled;
+        self
+    }
+
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    pub fn fields(&self) -> &WebpageSchema {
+        &self.fields
+    }
+
+    /// How the index was built. `None` for in-memory indexes and ones built before
+    /// `index_meta.json` was written.
+    pub fn index_meta(&self) -> Option<&IndexMeta> {
+        self.meta.as_ref()
+    }
+
+    /// Fields plain query words are matched against.
+    pub fn search_fields(&self) -> &[Field] {
+        &self.search_fields
+    }
+
+    pub fn query_parser(&self) -> &QueryParser {
+        &self.query_parser
+    }
+
+    /// Parses `query` with the title/body parser after synonym expansion, adding a
+    /// substring filter for every `url:fragment` clause and a range filter for every
+    /// `words:`, `pagerank:` or `crawled_at:` clause (see [`filters::extract_range_filters`]).
+    pub fn parse_query(&self, query: &str) -> Result<Box<dyn Query>, String> {
+        let (text, range_filters) = filters::extract_range_filters(query, &self.fields, &self.index.schema())?;
+        let (text, url_filters) = query::extract_url_filters(&text);
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        if !text.trim().is_empty() {
+            let parsed = self.query_parser.parse_query(&self.synonyms.expand(&text)).map_err(|e| e.to_string())?;
+            clauses.push((Occur::Must, parsed));
+        }
+        for (excluded, fragment) in url_filters {
+            let pattern = query::url_substring_pattern(&fragment);
+            let regex = RegexQuery::from_pattern(&pattern, self.fields.url)
+                .map_err(|e| format!("Invalid url filter '{}': {}", fragment, e))?;
+            clauses.push((if excluded { Occur::MustNot } else { Occur::Must }, Box::new(regex)));
+        }
+        clauses.extend(range_filters.into_iter().map(|filter| (Occur::Must, filter)));
+        match clauses.len() {
+            1 if clauses[0].0 == Occur::Must => Ok(clauses.remove(0).1),
+            _ => Ok(Box::new(BooleanQuery::new(clauses))),
+        }
+    }
+
+    /// Whether the index has the field called `name`. Older indexes lack the fields
+    /// added since they were built.
+    pub fn has_field(&self, name: &str) -> bool {
+        self.index.schema().get_field(name).is_ok()
+    }
+
+    /// Number of documents in the index.
+    pub fn num_docs(&self) -> u64 {
+        self.searcher().num_docs()
+    }
+
+    pub fn bm25(&self) -> bm25::Bm25Params {
+        self.bm25
+    }
+
+    /// Whether any document has a non-zero PageRank. Indexes built with `--no-pagerank`
+    /// store 0 for every page.
+    pub fn has_pagerank(&self) -> bool {
+        self.searcher().segment_readers().iter().any(|segment_reader| {
+            segment_reader.fast_fields().f64("pagerank").is_ok_and(|column| column.max_value() > 0.0)
+        })
+    }
+
+    /// Top `limit` matches of `query` by BM25 score, with partial pages scaled by
+    /// [`PARTIAL_WEIGHT`]. `words` are the analyzed query words, only needed to rescore
+    /// when the BM25 parameters differ from tantivy's defaults. With
+    /// [language normalization](Self::with_language_normalization) the scores are
+    /// relative to the best match of the same language.
+    pub fn top_by_relevance(
+        &self,
+        searcher: &tantivy::Searcher,
+        query: &dyn Query,
+        words: &[matched::QueryWord],
+        limit: usize,
+    ) -> tantivy::Result<Vec<(f32, DocAddress)>> {
+        let normalize = self.normalize_by_language && self.has_field("language");
+        let candidates = if normalize { limit * language_norm::CANDIDATES_PER_RESULT } else { limit };
+        let hits = if self.bm25.is_default() {
+            let collector = TopDocs::with_limit(candidates).tweak_score(|segment_reader: &SegmentReader| {
+                let weight = partial_weights(segment_reader);
+                move |doc: DocId, score: Score| score * weight(doc)
+            });
+            searcher.search(query, &collector)?
+        } else {
+            bm25::top_docs(searcher, query, &self.boosts, words, self.bm25, candidates)?
+        };
+        if !normalize {
+            return Ok(hits);
+        }
+        let language = |address: DocAddress| {
+            let doc: Option<TantivyDocument> = searcher.doc(address).ok();
+            doc.and_then(|doc| doc.get_first(self.fields.language).and_then(|v| v.as_str()).map(str::to_string))
+                .unwrap_or_default()
+        };
+        let mut hits = language_norm::normalize_per_language(hits, language);
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Touches every page of the index files so the OS page cache holds them, and
+    /// returns the number of bytes read. tantivy memory-maps the files, so reading them
+    /// through its own directory primes exactly the pages later queries fault in.
+    pub fn warm(&self) -> tantivy::Result<u64> {
+        const PAGE_SIZE: usize = 4096;
+        let directory = self.index.directory();
+        let mut total = 0u64;
+        for path in directory.list_managed_files() {
+            // Files listed in meta.json may already be gone after a merge
+            let Ok(file) = directory.open_read(&path) else { continue };
+            let bytes = file.read_bytes()?;
+            let checksum = bytes.as_slice().iter().step_by(PAGE_SIZE).fold(0u8, |acc, &b| acc ^ b);
+            std::hint::black_box(checksum);
+            total += bytes.len() as u64;
+        }
+        Ok(total)
+    }
+
+    /// A tantivy searcher over the current index snapshot. [`DocAddress`]es are only
+    /// valid within the snapshot they were found in.
+    pub fn searcher(&self) -> tantivy::Searcher {
+        self.reader.searcher()
+    }
+
+    /// Picks up the latest commit right away instead of within the milliseconds the
+    /// automatic reload takes, e.g. after committing to the index in this process.
+    pub fn reload(&self) -> tantivy::Result<()> {
+        self.reader.reload()
+    }
+
+    /// Terms of the body of the page indexed under `url` and how often each occurs, most
+    /// frequent first; empty if no page has that URL. Terms are in their indexed form
+    /// (stemmed, without stopwords), see [`similar::term_vector`].
+    pub fn term_vector(&self, url: &str) -> tantivy::Result<Vec<(String, u32)>> {
+        let searcher = self.searcher();
+        match similar::find_by_url(&searcher, self.fields.url, url)? {
+            Some(doc) => similar::term_vector(&searcher, self.fields.body, doc),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Indexed title/body terms starting with `prefix`, most common first.
+    /// Terms are returned in their indexed (stemmed) form.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut doc_freqs: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        for segment_reader in self.searcher().segment_readers() {
+            for field in [self.fields.title, self.fields.body] {
+                let Ok(inverted_index) = segment_reader.inverted_index(field) else { continue };
+                // Range scan from the prefix until the first term that no longer matches
+                let Ok(mut stream) = inverted_index.terms().range().ge(prefix.as_bytes()).into_stream() else { continue };
+                while stream.advance() {
+                    if !stream.key().starts_with(prefix.as_bytes()) { break; }
+                    if let Ok(term) = std::str::from_utf8(stream.key()) {
+                        *doc_freqs.entry(term.to_string()).or_insert(0) += stream.value().doc_freq;
+                    }
+                }
+            }
+        }
+
+        let mut terms: Vec<(String, u32)> = doc_freqs.into_iter().collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        terms.into_iter().take(limit).map(|(term, _)| term).collect()
+    }
+}
 
 /// Runs the interactive search prompt.
-pub fn run_searcher(index_path: &str) {
+pub fn run_searcher(index_path: &str, config: &SearchConfig) {
     println!("Loading search index from '{}'...", index_path);
     
-    let index = match Index::open_in_dir(index_path) {
-        Ok(index) => index,
+    let engine = match Searcher::open(index_path, config) {
+        Ok(engine) => engine,
         Err(e) => {
             eprintln!("Error: Failed to open index directory '{}'. {}", index_path, e);
             eprintln!("Please run the indexer first with: `cargo run`");
@@ -22,53 +476,224 @@ pub fn run_searcher(index_path: &str) {
         }
     };
 
-    // CRITICAL: We must register the "en_stem" tokenizer logic in the searcher too,
-    // otherwise it won't know how to parse the query words.
-    WebpageSchema::register_tokenizer(&index);
+    if engine.num_docs() == 0 {
+        eprintln!("The index in '{}' is empty, there is nothing to search.", index_path);
+        eprintln!("Crawl some pages first with: `cargo run -- index`");
+        return;
+    }
 
-    // Build fields helper to access field constants safely
-    let (_schema, fields) = WebpageSchema::build();
+    if config.warm {
+        let started = std::time::Instant::now();
+        match engine.warm() {
+            Ok(bytes) => println!(
+                "Warmed up {:.1} MB of index files in {} ms.",
+                bytes as f64 / 1_000_000.0,
+                started.elapsed().as_millis()
+            ),
+            Err(e) => eprintln!("Warning: index warm-up failed ({}).", e),
+        }
+    }
 
-    let reader = index.reader().expect("Failed to create index reader.");
-    let searcher = reader.searcher();
+    let query_log = match config.open_query_log() {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Error: Failed to open query log '{}'. {}", config.query_log.as_deref().unwrap_or_default(), e);
+            return;
+        }
+    };
 
-    // We search in Title and Body
-    let query_parser = QueryParser::for_index(&index, vec![fields.title, fields.body]);
+    let index = engine.index();
+    let fields = engine.fields();
+    let mut searcher = engine.searcher();
+
+    let vocabulary = match suggest::Vocabulary::build(&searcher, &[fields.title, fields.body]) {
+        Ok(vocabulary) => Some(vocabulary),
+        Err(e) => {
+            eprintln!("Warning: spelling suggestions disabled ({}).", e);
+            None
+        }
+    };
+    let mut analyzer = index.tokenizers().get("en_stem").expect("en_stem tokenizer is registered");
+    let has_pagerank = engine.has_pagerank();
+    let has_crawl_dates = engine.has_field("crawled_at");
 
     println!("Index loaded. Ready to search.");
+    if let Some(meta) = engine.index_meta() {
+        println!("{}", meta.summary());
+    }
+    println!("Syntax: \"exact phrase\", +required, -excluded (e.g. +rust \"async runtime\" -python)");
+    println!("        \"machine learning\"~3 lets the words of a phrase be up to 3 positions apart.");
+    println!("        title:word searches titles only, url:fragment keeps URLs containing the fragment.");
+    println!("        words:>500 filters by page length (also >=, <, <= or an exact count),");
+    println!("        pagerank:>0.001 by authority, crawled_at:[2024-01-01 TO *] by crawl date,");
+    println!("        status:200 by HTTP status and redirects:>0 for redirected pages (crawled with --record-redirects).");
+    println!("Type 'limit N' to change the number of results, 'sort relevance|pagerank|date' to change the order.");
+    println!("Type 'like URL' to find pages similar to an indexed page.");
+    println!("Type 'per-domain N|off' to cap results per domain, 'show all from DOMAIN' to see the rest.");
+    println!("Type 'debug N' to see how the N-th result's score was computed.");
     println!("Type 'exit' to quit.");
+    if !has_pagerank {
+        println!("This index was built without PageRank, 'sort pagerank' sorts by relevance.");
+    }
+    if !has_crawl_dates {
+        println!("This index has no crawl dates, 'sort date' sorts by relevance.");
+    }
+
+    let mut session = Session { max_per_domain: config.max_per_domain, ..Session::default() };
+    // Input of the last search, repeated by `show all from DOMAIN`
+    let mut last_query: Option<String> = None;
+    // Query and hits of the last result list, for `debug N`
+    let mut last_results: Option<(Box<dyn Query>, Vec<DocAddress>)> = None;
 
     loop {
         print!("\nSearch Query > ");
         io::stdout().flush().unwrap();
 
         let mut query_text = String::new();
-        if io::stdin().read_line(&mut query_text).is_err() {
-            continue;
+        match io::stdin().read_line(&mut query_text) {
+            // End of input (Ctrl-D or a closed pipe) ends the session like `exit`
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => continue,
         }
 
         let trimmed = query_text.trim();
         if trimmed.is_empty() { continue; }
         if trimmed.eq_ignore_ascii_case("exit") { break; }
 
-        // Parse the query
-        let query = match query_parser.parse_query(trimmed) {
-            Ok(q) => q,
-            Err(e) => {
-                eprintln!("Error parsing query: {}", e);
+        match session.apply_command(trimmed) {
+            Some(Ok(msg)) => { println!("{}", msg); continue; }
+            Some(Err(msg)) => { eprintln!("{}", msg); continue; }
+            None => {}
+        }
+
+        if let Some(n) = trimmed.strip_prefix("debug ") {
+            match &last_results {
+                Some((query, addresses)) => explain_result(&engine, &searcher, &**query, addresses, n.trim()),
+                None => eprintln!("Search for something first."),
+            }
+            continue;
+        }
+
+        // `show all from DOMAIN` repeats the last search on that domain only, without the cap
+        let (input, show_domain) = match trimmed.strip_prefix("show all from ") {
+            Some(domain) => match &last_query {
+                Some(previous) => (previous.clone(), Some(domain.trim().to_string())),
+                None => {
+                    eprintln!("Search for something first.");
+                    continue;
+                }
+            },
+            None => {
+                last_query = Some(trimmed.to_string());
+                (trimmed.to_string(), None)
+            }
+        };
+        let trimmed = input.as_str();
+
+        // `like URL` looks for pages similar to an indexed one instead of parsing a query
+        let like_url = trimmed.strip_prefix("like ").map(str::trim);
+        let (query, words) = if let Some(url) = like_url {
+            match similar::more_like_this(&searcher, fields.url, engine.search_fields(), url) {
+                Ok(like) => like,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            }
+        } else {
+            if let Err(msg) = query::validate_query(trimmed) {
+                eprintln!("Invalid query: {}", msg);
                 continue;
             }
+
+            // Parse the query. Phrases are tokenized (and stemmed) with the same analyzer as the
+            // indexed text, so positions line up and exact phrase matching works on stemmed fields.
+            match engine.parse_query(trimmed) {
+                Ok(q) => (q, matched::query_words(&mut analyzer, trimmed)),
+                Err(e) => {
+                    eprintln!("Error parsing query: {}", e);
+                    continue;
+                }
+            }
+        };
+
+        // Each search sees the latest commit, e.g. of a crawl still indexing in batches
+        let started = std::time::Instant::now();
+        last_results = None;
+        searcher = engine.searcher();
+        let query = match &show_domain {
+            Some(domain) => match diversity::restrict_to_domain(query, fields.url, domain) {
+                Ok(query) => query,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            },
+            None => query,
+        };
+        // Deduplicating and capping drop hits, so fetch extra candidates to still fill the list
+        let max_per_domain = session.max_per_domain.filter(|_| show_domain.is_none());
+        let fetch_limit = session.limit * match max_per_domain {
+            Some(_) => diversity::CANDIDATES_PER_RESULT,
+            None => diversity::DEDUP_CANDIDATES_PER_RESULT,
         };
 
         // Execute search. 
-        // We get the top 10 documents sorted by BM25 relevance score.
-        let top_docs = match searcher.search(&query, &TopDocs::with_limit(10)) {
+        // We get the top documents sorted by BM25 relevance score, PageRank or recency.
+        // Fast-field sorted hits carry no relevance score, so it's left empty for display.
+        let collector = TopDocs::with_limit(fetch_limit);
+        // Without PageRank every page ties, so sorting by it would only shuffle results
+        let sort = match session.sort {
+            SortOrder::PageRank if !has_pagerank => SortOrder::Relevance,
+            SortOrder::Date if !has_crawl_dates => SortOrder::Relevance,
+            sort => sort,
+        };
+        let top_docs: tantivy::Result<Vec<(Option<f32>, DocAddress)>> = match sort {
+            SortOrder::Relevance => engine
+                .top_by_relevance(&searcher, &*query, &words, fetch_limit)
+                .map(|docs| docs.into_iter().map(|(score, addr)| (Some(score), addr)).collect()),
+            SortOrder::PageRank => searcher
+                .search(&query, &collector.order_by_fast_field::<f64>("pagerank", Order::Desc))
+                .map(|docs| docs.into_iter().map(|(_, addr)| (None, addr)).collect()),
+            SortOrder::Date => searcher
+                .search(&query, &collector.order_by_fast_field::<i64>("crawled_at", Order::Desc))
+                .map(|docs| docs.into_iter().map(|(_, addr)| (None, addr)).collect()),
+        };
+
+        let top_docs = match top_docs {
             Ok(docs) => docs,
             Err(e) => {
                 eprintln!("Error executing search: {}", e);
                 continue;
             }
         };
+        let stored_url = |&(_, address): &(Option<f32>, DocAddress)| {
+            let doc: Option<TantivyDocument> = searcher.doc(address).ok();
+            doc.and_then(|doc| doc.get_first(fields.url).and_then(|v| v.as_str()).map(str::to_string)).unwrap_or_default()
+        };
+        let top_docs = diversity::dedup_by_url(top_docs, fetch_limit, stored_url);
+        let (top_docs, hidden) = match max_per_domain {
+            Some(max) => {
+                let capped = diversity::cap_per_domain(top_docs, max, session.limit, stored_url);
+                (capped.hits, capped.hidden)
+            }
+            None => (top_docs.into_iter().take(session.limit).collect(), Vec::new()),
+        };
+        if let Some(log) = &query_log
+            && show_domain.is_none()
+            && let Err(e) = log.record(trimmed, top_docs.len(), started.elapsed())
+        {
+            eprintln!("Warning: failed to log the query ({}).", e);
+        }
+
+        if top_docs.len() < FEW_RESULTS
+            && like_url.is_none()
+            && let Some(vocabulary) = &vocabulary
+            && let Some(suggestion) = suggest::did_you_mean(vocabulary, &mut analyzer, trimmed)
+        {
+            println!("Did you mean: {}?", suggestion);
+        }
 
         if top_docs.is_empty() {
             println!("No results found.");
@@ -76,9 +701,16 @@ pub fn run_searcher(index_path: &str) {
         }
         
         println!("\nFound {} results:", top_docs.len());
+        let addresses: Vec<DocAddress> = top_docs.iter().map(|&(_, address)| address).collect();
 
-        for (score, doc_address) in top_docs {
-            let retrieved_doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+        for (n, (score, doc_address)) in top_docs.into_iter().enumerate() {
+            let retrieved_doc: TantivyDocument = match searcher.doc(doc_address) {
+                Ok(doc) => doc,
+                Err(e) => {
+                    eprintln!("Error loading result {}: {}", n + 1, e);
+                    continue;
+                }
+            };
             
             // Helper to extract string fields
             let get_text = |field| {
@@ -98,11 +730,117 @@ pub fn run_searcher(index_path: &str) {
             let url = get_text(fields.url);
             let lang = get_text(fields.language);
             let pr = get_f64(fields.pagerank);
+            let crawled = retrieved_doc.get_first(fields.crawled_at)
+                .and_then(|v| v.as_i64())
+                .map(format_timestamp)
+                .unwrap_or_else(|| "[Unknown]".to_string());
+            let words_count = retrieved_doc.get_first(fields.word_count)
+                .and_then(|v| v.as_i64())
+                .map_or("?".to_string(), |n| n.to_string());
+            let relevance = score.map_or("-".to_string(), |s| format!("{:.4}", s));
 
-            println!("------------------------------------------------");
-            println!("Title:    {}", title);
+            println!("{:-<48}", format!("--- #{} ", n + 1));
+            let partial = retrieved_doc.get_first(fields.partial).and_then(|v| v.as_bool()).unwrap_or(false);
+            println!("Title:    {}{}", title, if partial { " [partial content]" } else { "" });
             println!("URL:      {}", url);
-            println!("Relevance: {:.4} | PageRank: {:.6} | Lang: {}", score, pr, lang);
+            if let Some(favicon) = retrieved_doc.get_first(fields.favicon).and_then(|v| v.as_str()) {
+                println!("Icon:     {}", favicon);
+            }
+            println!("Crawled:  {} | Words: {}", crawled, words_count);
+            let chain: Vec<&str> = retrieved_doc.get_all(fields.redirected_from).filter_map(|v| v.as_str()).collect();
+            if !chain.is_empty() {
+                println!("Via:      {}", chain.join(" -> "));
+            }
+            println!("Relevance: {} | PageRank: {:.6} | Lang: {}", relevance, pr, lang);
+
+            if !words.is_empty() {
+                let (found, missing) = matched::matched_terms(&searcher, engine.search_fields(), doc_address, &words);
+                // A `like` query is made of many terms, any of which may match, so only list the hits
+                if missing.is_empty() || like_url.is_some() {
+                    println!("Matched:  {}", found.join(", "));
+                } else {
+                    println!("Matched:  {} (missing: {})", found.join(", "), missing.join(", "));
+                }
+            }
+        }
+
+        if !hidden.is_empty() {
+            let more: Vec<String> = hidden.iter().map(|(domain, n)| format!("{} (+{})", domain, n)).collect();
+            println!("------------------------------------------------");
+            println!("More from: {}. Type 'show all from DOMAIN' to see them.", more.join(", "));
         }
+
+        // Per-language breakdown of all matches, to hint at a `language:xx` filter
+        let language_counts = if engine.has_field("language") {
+            facets::language_counts(&searcher, &*query, fields.language)
+        } else {
+            Ok(Vec::new())
+        };
+        match language_counts {
+            Ok(counts) if !counts.is_empty() => {
+                let summary: Vec<String> = counts.iter().map(|(lang, n)| format!("{}: {}", lang, n)).collect();
+                println!("------------------------------------------------");
+                println!("Languages: {}", summary.join(", "));
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Error counting languages: {}", e),
+        }
+
+        match related::related_terms(&searcher, &mut analyzer, fields.title, &addresses, &words, RELATED_TERMS) {
+            Ok(terms) if !terms.is_empty() => println!("Related: {}", terms.join(", ")),
+            Ok(_) => {}
+            Err(e) => eprintln!("Error finding related terms: {}", e),
+        }
+        last_results = Some((query, addresses));
+    }
+}
+
+/// Prints tantivy's score breakdown (term frequency, IDF, field norm per matched term)
+/// of the `n`-th (1-based) of `addresses` for `query`.
+fn explain_result(engine: &Searcher, searcher: &tantivy::Searcher, query: &dyn Query, addresses: &[DocAddress], n: &str) {
+    let address = match n.parse::<usize>() {
+        Ok(n) if (1..=addresses.len()).contains(&n) => addresses[n - 1],
+        _ => {
+            eprintln!("Invalid result number '{}'. Use 1 to {}.", n, addresses.len());
+            return;
+        }
+    };
+    match query.explain(searcher, address) {
+        Ok(explanation) => println!("{}", explanation.to_pretty_json()),
+        Err(e) => {
+            eprintln!("Error explaining the score: {}", e);
+            return;
+        }
+    }
+    // Both adjustments happen outside the query, so tantivy doesn't know about them
+    if !engine.bm25().is_default() {
+        println!("Note: results were rescored with k1={} b={}, the breakdown uses tantivy's defaults.", engine.bm25().k1, engine.bm25().b);
+    }
+    let partial = searcher
+        .doc::<TantivyDocument>(address)
+        .ok()
+        .and_then(|doc| doc.get_first(engine.fields().partial).and_then(|v| v.as_bool()))
+        .unwrap_or(false);
+    if partial {
+        println!("Note: this is a partial page, its relevance is further multiplied by {}.", PARTIAL_WEIGHT);
+    }
+}
+
+/// Every field of `schema` must sit at the same position, with the same name and type,
+/// as in [`WebpageSchema`], since the searcher addresses fields by position.
+pub(crate) fn check_schema(schema: &Schema) -> Result<(), String> {
+    let (current, _) = WebpageSchema::build();
+    for (field, entry) in schema.fields() {
+        let expected = current.fields().nth(field.field_id() as usize).map(|(_, e)| e);
+        let compatible = expected.is_some_and(|expected| {
+            expected.name() == entry.name() && expected.field_type().value_type() == entry.field_type().value_type()
+        });
+        if !compatible {
+            return Err(format!("The index has an unexpected field '{}'. Rebuild it with `cargo run -- index`.", entry.name()));
+        }
+    }
+    if let Some(missing) = REQUIRED_FIELDS.iter().find(|f| schema.get_field(f).is_err()) {
+        return Err(format!("The index has no '{}' field. Rebuild it with `cargo run -- index`.", missing));
     }
-}
\ No newline at end of file
+    Ok(())
+}
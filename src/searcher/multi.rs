@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use tantivy::schema::Value;
+use tantivy::{DocAddress, Score, TantivyDocument, TantivyError};
+
+use super::{matched, query, SearchConfig, Searcher};
+
+/// One result of a [`MultiSearcher`] query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiHit {
+    /// Name of the index the document came from.
+    pub index: String,
+    pub score: Score,
+    pub url: String,
+    pub title: String,
+    /// Address within the index named by [`index`](Self::index).
+    pub address: DocAddress,
+}
+
+/// Searches several indexes, e.g. one per topic, as if they were one.
+///
+/// Every index is queried for the top `limit` matches and the lists are merged by
+/// score. BM25 statistics stay per index, so a term that is rare in one index scores
+/// higher there than in an index where it's common; that's usually what partitioned
+/// crawls want, but scores are not comparable to those of a single combined index.
+pub struct MultiSearcher {
+    indexes: Vec<(String, Searcher)>,
+}
+
+impl MultiSearcher {
+    /// Opens the index in every directory of `paths`, each named after its directory.
+    pub fn open<P: AsRef<Path>>(paths: &[P], config: &SearchConfig) -> tantivy::Result<Self> {
+        let mut indexes = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+            let searcher = Searcher::open(&path.to_string_lossy(), config).map_err(|e| match e {
+                TantivyError::SchemaError(msg) => TantivyError::SchemaError(format!("{}: {}", name, msg)),
+                e => e,
+            })?;
+            indexes.push((name, searcher));
+        }
+        Self::new(indexes)
+    }
+
+    /// Combines already opened searchers, whose schemas [`Searcher::from_index`] already
+    /// checked. Fails if there are none.
+    pub fn new(indexes: Vec<(String, Searcher)>) -> tantivy::Result<Self> {
+        if indexes.is_empty() {
+            return Err(TantivyError::InvalidArgument("No index to search.".to_string()));
+        }
+        Ok(Self { indexes })
+    }
+
+    pub fn indexes(&self) -> &[(String, Searcher)] {
+        &self.indexes
+    }
+
+    /// Top `limit` matches of `query` over all indexes by relevance, best first.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<MultiHit>, String> {
+        let mut hits = Vec::new();
+        for (name, engine) in &self.indexes {
+            let parsed = engine.parse_query(query).map_err(|e| format!("{}: {}", name, e))?;
+            let mut analyzer = engine.index().tokenizers().get("en_stem").expect("en_stem tokenizer is registered");
+            let words = matched::query_words(&mut analyzer, query);
+            let searcher = engine.searcher();
+            let top_docs = engine
+                .top_by_relevance(&searcher, &*parsed, &words, limit)
+                .map_err(|e| format!("{}: {}", name, e))?;
+            for (score, address) in top_docs {
+                let doc: TantivyDocument = searcher.doc(address).map_err(|e| format!("{}: {}", name, e))?;
+                let text = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                hits.push(MultiHit {
+                    index: name.clone(),
+                    score,
+                    url: text(engine.fields().url),
+                    title: text(engine.fields().title),
+                    address,
+                });
+            }
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+}
+
+/// Runs a search prompt over all indexes in `index_paths`, ranked by relevance.
+pub fn run_multi_searcher<P: AsRef<Path>>(index_paths: &[P], config: &SearchConfig) {
+    let engine = match MultiSearcher::open(index_paths, config) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Error: Failed to open indexes. {}", e);
+            return;
+        }
+    };
+    let names: Vec<&str> = engine.indexes().iter().map(|(name, _)| name.as_str()).collect();
+    println!("Searching {} indexes: {}", names.len(), names.join(", "));
+    println!("Type 'limit N' to change the number of results, 'exit' to quit.");
+
+    let mut limit = super::DEFAULT_LIMIT;
+    loop {
+        print!("\nSearch Query > ");
+        io::stdout().flush().unwrap();
+
+        let mut query_text = String::new();
+        match io::stdin().read_line(&mut query_text) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => continue,
+        }
+
+        let trimmed = query_text.trim();
+        if trimmed.is_empty() { continue; }
+        if trimmed.eq_ignore_ascii_case("exit") { break; }
+        if let Some(n) = trimmed.strip_prefix("limit ") {
+            match n.trim().parse::<usize>() {
+                Ok(n) if n > 0 => {
+                    limit = n;
+                    println!("Showing up to {} results.", limit);
+                }
+                _ => eprintln!("Invalid limit '{}'. Use a positive number.", n.trim()),
+            }
+            continue;
+        }
+        if let Err(msg) = query::validate_query(trimmed) {
+            eprintln!("Invalid query: {}", msg);
+            continue;
+        }
+
+        let hits = match engine.search(trimmed, limit) {
+            Ok(hits) => hits,
+            Err(e) => {
+                eprintln!("Error executing search: {}", e);
+                continue;
+            }
+        };
+        if hits.is_empty() {
+            println!("No results found.");
+            continue;
+        }
+
+        println!("\nFound {} results:", hits.len());
+        for hit in hits {
+            println!("------------------------------------------------");
+            println!("Title:    {}", hit.title);
+            println!("URL:      {}", hit.url);
+            println!("Index:    {} | Relevance: {:.4}", hit.index, hit.score);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::{self, Write};
+use std::time::Instant;
+
+use serde_json::Value as Json;
+use tantivy::schema::{Field, Schema, Value};
+use tantivy::{Score, TantivyDocument};
+
+use super::{diversity, matched, query, SearchConfig, Searcher};
+
+/// Fields printed when `--fields` isn't given.
+pub const DEFAULT_FIELDS: &str = "url,title,score";
+/// Not a stored field but the relevance score of the hit.
+pub const SCORE: &str = "score";
+
+/// Which fields of a result to output, in order: `score` or any stored field of the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projection {
+    fields: Vec<(String, Option<Field>)>,
+}
+
+impl Projection {
+    /// Parses a comma-separated list like `url,title,score`, rejecting fields the index
+    /// doesn't have or doesn't store (the body is indexed only).
+    pub fn parse(spec: &str, schema: &Schema) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        for name in spec.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if fields.iter().any(|(n, _)| n == name) {
+                continue;
+            }
+            let field = match name {
+                SCORE => None,
+                name => match schema.get_field(name) {
+                    Ok(field) if schema.get_field_entry(field).is_stored() => Some(field),
+                    _ => return Err(format!("Unknown field '{}'. Available: {}.", name, available(schema).join(", "))),
+                },
+            };
+            fields.push((name.to_string(), field));
+        }
+        if fields.is_empty() {
+            return Err("No fields selected.".to_string());
+        }
+        Ok(Self { fields })
+    }
+
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The selected fields of `doc` and their values, in the selected order. Missing
+    /// values are `null` and fields with several values (e.g. `schema_type`) are arrays.
+    pub fn project(&self, doc: &TantivyDocument, score: Option<Score>) -> Vec<(&str, Json)> {
+        self.fields
+            .iter()
+            .map(|(name, field)| {
+                let value = match field {
+                    None => score.map_or(Json::Null, |s| Json::from(f64::from(s))),
+                    Some(field) => {
+                        let mut values: Vec<Json> = doc.get_all(*field).map(to_json).collect();
+                        match values.len() {
+                            0 => Json::Null,
+                            1 => values.remove(0),
+                            _ => Json::Array(values),
+                        }
+                    }
+                };
+                (name.as_str(), value)
+            })
+            .collect()
+    }
+
+    /// [`project`](Self::project) as a single-line JSON object, keys in the selected order.
+    pub fn to_json_line(&self, doc: &TantivyDocument, score: Option<Score>) -> String {
+        let members: Vec<String> = self.project(doc, score)
+            .into_iter()
+            .map(|(name, value)| format!("{}:{}", Json::from(name), value))
+            .collect();
+        format!("{{{}}}", members.join(","))
+    }
+}
+
+/// `score` and the stored fields of `schema`, the names [`Projection::parse`] accepts.
+fn available(schema: &Schema) -> Vec<&str> {
+    let stored = schema.fields().filter(|(_, entry)| entry.is_stored()).map(|(_, entry)| entry.name());
+    std::iter::once(SCORE).chain(stored).collect()
+}
+
+fn to_json<'a>(value: impl Value<'a>) -> Json {
+    if let Some(text) = value.as_str() {
+        Json::from(text)
+    } else if let Some(n) = value.as_i64() {
+        Json::from(n)
+    } else if let Some(n) = value.as_u64() {
+        Json::from(n)
+    } else if let Some(x) = value.as_f64() {
+        Json::from(x)
+    } else if let Some(b) = value.as_bool() {
+        Json::from(b)
+    } else {
+        Json::Null
+    }
+}
+
+/// Runs `query_text` once against the index at `index_path` and prints the top `limit`
+/// results by relevance as JSON lines with the fields in `fields` (see
+/// [`Projection::parse`]), for scripts. Returns the number of results printed.
+pub fn run_json_query(index_path: &str, query_text: &str, limit: usize, fields: &str, config: &SearchConfig) -> Result<usize, String> {
+    let engine = Searcher::open(index_path, config).map_err(|e| format!("Failed to open index directory '{}'. {}", index_path, e))?;
+    let query_log = config
+        .open_query_log()
+        .map_err(|e| format!("Failed to open query log '{}'. {}", config.query_log.as_deref().unwrap_or_default(), e))?;
+    let projection = Projection::parse(fields, &engine.index().schema())?;
+    query::validate_query(query_text).map_err(|msg| format!("Invalid query: {}", msg))?;
+    let parsed = engine.parse_query(query_text)?;
+
+    let mut analyzer = engine.index().tokenizers().get("en_stem").expect("en_stem tokenizer is registered");
+    let words = matched::query_words(&mut analyzer, query_text);
+    let started = Instant::now();
+    let searcher = engine.searcher();
+    let hits = engine
+        .top_by_relevance(&searcher, &*parsed, &words, limit * diversity::DEDUP_CANDIDATES_PER_RESULT)
+        .map_err(|e| e.to_string())?;
+
+    let mut docs = Vec::with_capacity(hits.len());
+    for (score, address) in hits {
+        let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+        docs.push((score, doc));
+    }
+    let url = |(_, doc): &(Score, TantivyDocument)| {
+        doc.get_first(engine.fields().url).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+    };
+    let docs = diversity::dedup_by_url(docs, limit, url);
+    if let Some(log) = &query_log {
+        log.record(query_text, docs.len(), started.elapsed()).map_err(|e| format!("Failed to log the query. {}", e))?;
+    }
+
+    let mut out = io::stdout().lock();
+    for (score, doc) in &docs {
+        writeln!(out, "{}", projection.to_json_line(doc, Some(*score))).map_err(|e| e.to_string())?;
+    }
+    Ok(docs.len())
+}
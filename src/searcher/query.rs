@@ -0,0 +1,115 @@
+// Helpers for interpreting the raw text typed at the search prompt.
+
+/// Checks the query syntax we document in the prompt banner before handing it to tantivy:
+/// - `"machine learning"` matches the exact phrase (positions are indexed for title and body)
+/// - `+term` requires the term, `-term` excludes it
+///
+/// Returns a human-readable explanation when the query can't be run as the user expects.
+pub fn validate_query(query: &str) -> Result<(), String> {
+    if !query.matches('"').count().is_multiple_of(2) {
+        return Err("Unbalanced quotes. Close the phrase with a second '\"'.".to_string());
+    }
+
+    let mut has_positive_clause = false;
+    for token in split_clauses(query) {
+        match token.strip_prefix(['+', '-']) {
+            Some("") => {
+                return Err(format!("Operator '{}' must be followed by a term or phrase.", token));
+            }
+            Some(_) if token.starts_with('-') => {}
+            _ => has_positive_clause = true,
+        }
+    }
+
+    // A boolean query made only of exclusions matches nothing in tantivy.
+    if !has_positive_clause {
+        return Err("Query has only excluded terms. Add at least one term to search for.".to_string());
+    }
+
+    Ok(())
+}
+
+/// Characters with a meaning in tantivy's regex syntax.
+const REGEX_META: &[char] = &['\\', '.', '+', '*', '?', '(', ')', '|', '[', ']', '{', '}', '^', '$', '#', '&', '-', '~'];
+
+/// Removes the `url:fragment` clauses from `query`, returning the remaining text and the
+/// fragments with whether they were excluded (`-url:fragment`).
+///
+/// The url field is indexed as a single raw token, which the query parser can only
+/// match exactly, so these clauses are turned into substring regexes instead.
+pub fn extract_url_filters(query: &str) -> (String, Vec<(bool, String)>) {
+    let mut rest = Vec::new();
+    let mut filters = Vec::new();
+    for clause in split_clauses(query) {
+        let (excluded, body) = match clause.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (false, clause.strip_prefix('+').unwrap_or(clause)),
+        };
+        match body.strip_prefix("url:") {
+            Some(fragment) if !fragment.is_empty() => filters.push((excluded, fragment.trim_matches('"').to_string())),
+            _ => rest.push(clause),
+        }
+    }
+    (rest.join(" "), filters)
+}
+
+/// A case-insensitive regex matching any URL that contains `fragment`.
+pub fn url_substring_pattern(fragment: &str) -> String {
+    format!("(?i).*{}.*", regex_escape(fragment))
+}
+
+/// `text` with regex metacharacters escaped, so it only matches itself.
+pub fn regex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if REGEX_META.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Splits a query into whitespace separated clauses, keeping quoted phrases and
+/// `[a TO b]` ranges together.
+pub fn split_clauses(query: &str) -> Vec<&str> {
+    let mut clauses = Vec::new();
+    let mut in_phrase = false;
+    let mut in_range = false;
+    let mut start = None;
+
+    for (i, c) in query.char_indices() {
+        match c {
+            '"' if !in_range => in_phrase = !in_phrase,
+            '[' | '{' if !in_phrase => in_range = true,
+            ']' | '}' if !in_phrase => in_range = false,
+            _ => {}
+        }
+        if c.is_whitespace() && !in_phrase && !in_range {
+            if let Some(s) = start.take() {
+                clauses.push(&query[s..i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        clauses.push(&query[s..]);
+    }
+    clauses
+}
+
+/// The plain words a user is searching for: excluded (`-term`) and field-scoped
+/// (`title:foo`) clauses are skipped, operators, quotes and phrase slops (`"a b"~3`)
+/// are stripped.
+pub fn positive_words(query: &str) -> impl Iterator<Item = &str> {
+    query
+        .split_whitespace()
+        .filter(|raw| !raw.starts_with('-') && !raw.contains(':'))
+        .map(|raw| match raw.rsplit_once("\"~") {
+            Some((word, slop)) if slop.chars().all(|c| c.is_ascii_digit()) => word,
+            _ => raw,
+        })
+        .map(|raw| raw.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Queries listed per section of a [`QueryReport`] by default.
+pub const DEFAULT_REPORT_LIMIT: usize = 20;
+
+/// One line of the query log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryLogEntry {
+    pub query: String,
+    /// Results shown, after deduplication and the per-domain cap.
+    pub results: usize,
+    pub latency_ms: u64,
+    /// Unix seconds.
+    pub timestamp: i64,
+}
+
+/// Appends every search to an NDJSON file, one [`QueryLogEntry`] per line.
+///
+/// Only written when enabled with [`SearchConfig::query_log`](super::SearchConfig::query_log):
+/// queries say a lot about the people typing them, so nothing is logged by default.
+#[derive(Debug, Clone)]
+pub struct QueryLog(Arc<Mutex<File>>);
+
+impl QueryLog {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self(Arc::new(Mutex::new(file))))
+    }
+
+    /// Writes one entry. Each line is written in one go so it's never cut off by a crash.
+    pub fn record(&self, query: &str, results: usize, latency: Duration) -> io::Result<()> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+        let entry = QueryLogEntry { query: query.to_string(), results, latency_ms: latency.as_millis() as u64, timestamp };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).write_all(line.as_bytes())
+    }
+}
+
+/// Aggregate of a query log: which queries are asked most and which find nothing. The
+/// latter point at content worth crawling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryReport {
+    pub total_queries: usize,
+    pub distinct_queries: usize,
+    /// Queries that returned no results at all.
+    pub zero_result_queries: usize,
+    pub average_latency_ms: f64,
+    /// Most frequent queries with their count, most frequent first (ties alphabetically).
+    pub popular: Vec<(String, usize)>,
+    /// Most frequent queries that found nothing, ordered like `popular`.
+    pub zero_results: Vec<(String, usize)>,
+}
+
+impl QueryReport {
+    /// Aggregates `entries`, listing at most `limit` queries per section. Queries are
+    /// compared case-insensitively with whitespace collapsed, so `Rust  async` and
+    /// `rust async` count as one.
+    pub fn from_entries(entries: impl IntoIterator<Item = QueryLogEntry>, limit: usize) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut zero: HashMap<String, usize> = HashMap::new();
+        let (mut total, mut zero_total, mut latency) = (0, 0, 0u64);
+        for entry in entries {
+            let query = normalize_query(&entry.query);
+            total += 1;
+            latency += entry.latency_ms;
+            if entry.results == 0 {
+                zero_total += 1;
+                *zero.entry(query.clone()).or_insert(0) += 1;
+            }
+            *counts.entry(query).or_insert(0) += 1;
+        }
+        Self {
+            total_queries: total,
+            distinct_queries: counts.len(),
+            zero_result_queries: zero_total,
+            average_latency_ms: if total > 0 { latency as f64 / total as f64 } else { 0.0 },
+            popular: most_frequent(counts, limit),
+            zero_results: most_frequent(zero, limit),
+        }
+    }
+
+    /// Reads and aggregates the log at `path`. A line that isn't an entry fails with its
+    /// line number; blank lines are skipped.
+    pub fn from_log(path: &Path, limit: usize) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for (n, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", n + 1, e)))?;
+            entries.push(entry);
+        }
+        Ok(Self::from_entries(entries, limit))
+    }
+}
+
+impl std::fmt::Display for QueryReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} queries ({} distinct), {} with no results, {:.1} ms on average.",
+            self.total_queries, self.distinct_queries, self.zero_result_queries, self.average_latency_ms
+        )?;
+        for (title, queries) in [("Most frequent queries:", &self.popular), ("Queries without results:", &self.zero_results)] {
+            writeln!(f, "\n{}", title)?;
+            if queries.is_empty() {
+                writeln!(f, "  (none)")?;
+            }
+            for (query, count) in queries {
+                writeln!(f, "  {:>6}  {}", count, query)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn most_frequent(counts: HashMap<String, usize>, limit: usize) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(limit);
+    counts
+}
+
+/// Prints the [`QueryReport`] of the log at `path`.
+pub fn run_report(path: &str, limit: usize) {
+    match QueryReport::from_log(Path::new(path), limit) {
+        Ok(report) => print!("{}", report),
+        Err(e) => eprintln!("Failed to read query log '{}': {}", path, e),
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::{HashMap, HashSet};
+use tantivy::schema::{Field, Value};
+use tantivy::tokenizer::TextAnalyzer;
+use tantivy::{DocAddress, TantivyDocument, Term};
+
+use super::matched::QueryWord;
+
+/// How many of the top results a term must appear in to be suggested.
+const MIN_RESULT_DOCS: usize = 2;
+
+/// Terms that are much more common among `docs` (the top results) than in the index as a
+/// whole, best first, as query refinement hints.
+///
+/// The body isn't stored, so the titles stand in for snippets: each title is analyzed
+/// like the indexed text and a term scores its share of result titles containing it
+/// minus its share of all titles. Terms are returned stemmed, and the query's own
+/// `words` are left out.
+pub fn related_terms(
+    searcher: &tantivy::Searcher,
+    analyzer: &mut TextAnalyzer,
+    title_field: Field,
+    docs: &[DocAddress],
+    words: &[QueryWord],
+    limit: usize,
+) -> tantivy::Result<Vec<String>> {
+    if docs.len() < MIN_RESULT_DOCS {
+        return Ok(Vec::new());
+    }
+
+    let mut result_freqs: HashMap<String, usize> = HashMap::new();
+    for &address in docs {
+        let doc: TantivyDocument = searcher.doc(address)?;
+        let Some(title) = doc.get_first(title_field).and_then(|v| v.as_str()) else { continue };
+        let mut tokens = HashSet::new();
+        let mut stream = analyzer.token_stream(title);
+        while let Some(token) = stream.next() {
+            tokens.insert(token.text.clone());
+        }
+        for token in tokens {
+            *result_freqs.entry(token).or_insert(0) += 1;
+        }
+    }
+
+    let total_docs = searcher.num_docs().max(1) as f64;
+    let mut scored = Vec::new();
+    for (token, freq) in result_freqs {
+        let is_query_word = words.iter().any(|w| w.token == token);
+        if freq < MIN_RESULT_DOCS || is_query_word || token.chars().count() < 3 || token.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let global = searcher.doc_freq(&Term::from_field_text(title_field, &token))? as f64 / total_docs;
+        let score = freq as f64 / docs.len() as f64 - global;
+        if score > 0.0 {
+            scored.push((token, score));
+        }
+    }
+
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(scored.into_iter().take(limit).map(|(token, _)| token).collect())
+}
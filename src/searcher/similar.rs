@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use tantivy::collector::TopDocs;
+use tantivy::postings::Postings;
+use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocAddress, DocSet, Score, Term};
+
+use super::matched::QueryWord;
+
+/// Terms of the source page a "more like this" query is built from.
+pub const MORE_LIKE_THIS_TERMS: usize = 20;
+
+/// The indexed page whose URL is exactly `url`.
+pub fn find_by_url(searcher: &tantivy::Searcher, url_field: Field, url: &str) -> tantivy::Result<Option<DocAddress>> {
+    let query = TermQuery::new(Term::from_field_text(url_field, url), IndexRecordOption::Basic);
+    let hits = searcher.search(&query, &TopDocs::with_limit(1))?;
+    Ok(hits.first().map(|&(_, address)| address))
+}
+
+/// Calls `f` with every term of `doc` in `field` and its frequency there, in term order.
+///
+/// The body isn't stored, so the page's terms are recovered from the index by probing
+/// the postings of every term in its segment. That reads the whole term dictionary,
+/// which is fine for the index sizes we crawl but not meant for every query.
+pub fn for_each_doc_term(
+    searcher: &tantivy::Searcher,
+    field: Field,
+    doc: DocAddress,
+    mut f: impl FnMut(&str, u32) -> tantivy::Result<()>,
+) -> tantivy::Result<()> {
+    let inverted_index = searcher.segment_reader(doc.segment_ord).inverted_index(field)?;
+    let mut stream = inverted_index.terms().stream()?;
+    while stream.advance() {
+        let Ok(token) = std::str::from_utf8(stream.key()) else { continue };
+        let mut postings = inverted_index.read_postings_from_terminfo(stream.value(), IndexRecordOption::WithFreqs)?;
+        // `seek` only moves forward, so a list already past the doc doesn't contain it
+        if postings.doc() > doc.doc_id || postings.seek(doc.doc_id) != doc.doc_id {
+            continue;
+        }
+        f(token, postings.term_freq())?;
+    }
+    Ok(())
+}
+
+/// Every term of `doc` in `field` with its frequency, most frequent first (ties
+/// alphabetically). Terms are in their indexed form: stemmed, without stopwords.
+pub fn term_vector(searcher: &tantivy::Searcher, field: Field, doc: DocAddress) -> tantivy::Result<Vec<(String, u32)>> {
+    let mut terms = Vec::new();
+    for_each_doc_term(searcher, field, doc, |token, freq| {
+        terms.push((token.to_string(), freq));
+        Ok(())
+    })?;
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(terms)
+}
+
+/// The `limit` terms of `doc` in `fields` that best characterize it, best first, with
+/// their tf-idf weight. Terms no other page contains are left out since they can't
+/// match anything else. Reads the whole term dictionary, see [`for_each_doc_term`].
+pub fn distinctive_terms(
+    searcher: &tantivy::Searcher,
+    fields: &[Field],
+    doc: DocAddress,
+    limit: usize,
+) -> tantivy::Result<Vec<(String, Score)>> {
+    let total_docs = searcher.num_docs().max(1) as Score;
+    let mut weights: HashMap<String, Score> = HashMap::new();
+    for &field in fields {
+        for_each_doc_term(searcher, field, doc, |token, freq| {
+            if token.chars().count() < 3 || token.chars().all(|c| c.is_ascii_digit()) {
+                return Ok(());
+            }
+            let doc_freq = searcher.doc_freq(&Term::from_field_text(field, token))?;
+            if doc_freq < 2 {
+                return Ok(());
+            }
+            let idf = (total_docs / doc_freq as Score).ln() + 1.0;
+            *weights.entry(token.to_string()).or_insert(0.0) += (freq as Score).sqrt() * idf;
+            Ok(())
+        })?;
+    }
+
+    let mut terms: Vec<(String, Score)> = weights.into_iter().collect();
+    terms.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    terms.truncate(limit);
+    Ok(terms)
+}
+
+/// A query for pages sharing the distinctive terms of the page at `url`, excluding that
+/// page itself, along with the terms as query words (for rescoring and the "Matched"
+/// line). Each term is boosted by its weight relative to the strongest one.
+pub fn more_like_this(
+    searcher: &tantivy::Searcher,
+    url_field: Field,
+    fields: &[Field],
+    url: &str,
+) -> Result<(Box<dyn Query>, Vec<QueryWord>), String> {
+    let source = find_by_url(searcher, url_field, url)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No indexed page has the URL '{}'.", url))?;
+    let terms = distinctive_terms(searcher, fields, source, MORE_LIKE_THIS_TERMS).map_err(|e| e.to_string())?;
+    let Some(&(_, max_weight)) = terms.first() else {
+        return Err(format!("'{}' shares no terms with other pages.", url));
+    };
+
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for (token, weight) in &terms {
+        for &field in fields {
+            let query = TermQuery::new(Term::from_field_text(field, token), IndexRecordOption::WithFreqs);
+            clauses.push((Occur::Should, Box::new(BoostQuery::new(Box::new(query), weight / max_weight))));
+        }
+    }
+    let source_url = TermQuery::new(Term::from_field_text(url_field, url), IndexRecordOption::Basic);
+    clauses.push((Occur::MustNot, Box::new(source_url)));
+
+    let words = terms.into_iter().map(|(token, _)| QueryWord { word: token.clone(), token }).collect();
+    Ok((Box::new(BooleanQuery::new(clauses)), words))
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use tantivy::schema::Field;
+use tantivy::tokenizer::TextAnalyzer;
+
+use super::query::positive_words;
+
+/// Largest edit distance we still consider a typo.
+const MAX_EDIT_DISTANCE: usize = 2;
+/// Very rare terms are often typos themselves, so they make poor suggestions.
+const MIN_SUGGESTION_DOC_FREQ: u32 = 3;
+
+/// Every term of the searchable fields with its document frequency,
+/// read from the inverted index when the searcher starts.
+pub struct Vocabulary {
+    terms: HashMap<String, u32>,
+}
+
+impl Vocabulary {
+    pub fn build(searcher: &tantivy::Searcher, fields: &[Field]) -> tantivy::Result<Self> {
+        let mut terms: HashMap<String, u32> = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            for &field in fields {
+                let inverted_index = segment_reader.inverted_index(field)?;
+                let mut stream = inverted_index.terms().stream()?;
+                while stream.advance() {
+                    if let Ok(term) = std::str::from_utf8(stream.key()) {
+                        *terms.entry(term.to_string()).or_insert(0) += stream.value().doc_freq;
+                    }
+                }
+            }
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn doc_freq(&self, term: &str) -> u32 {
+        self.terms.get(term).copied().unwrap_or(0)
+    }
+
+    /// The frequent indexed term closest to `term`, if it's within a small edit distance.
+    pub fn closest(&self, term: &str) -> Option<&str> {
+        let len = term.chars().count();
+        self.terms
+            .iter()
+            .filter(|(_, freq)| **freq >= MIN_SUGGESTION_DOC_FREQ)
+            .filter(|(candidate, _)| candidate.chars().count().abs_diff(len) <= MAX_EDIT_DISTANCE)
+            .map(|(candidate, &freq)| (levenshtein(term, candidate), freq, candidate))
+            .filter(|(dist, _, _)| *dist <= MAX_EDIT_DISTANCE)
+            // Closest first, then the most common
+            .min_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)).then_with(|| a.2.cmp(b.2)))
+            .map(|(_, _, candidate)| candidate.as_str())
+    }
+}
+
+/// Rewrites `query` with every unmatched word replaced by its closest indexed term.
+/// Returns `None` when nothing could be corrected.
+pub fn did_you_mean(vocabulary: &Vocabulary, analyzer: &mut TextAnalyzer, query: &str) -> Option<String> {
+    let mut corrected = query.to_string();
+    let mut changed = false;
+
+    for word in positive_words(query) {
+        // Compare in the indexed (lowercased, stemmed) form; stopwords produce no token.
+        let mut stream = analyzer.token_stream(word);
+        let Some(token) = stream.next().map(|t| t.text.clone()) else {
+            continue;
+        };
+        if vocabulary.doc_freq(&token) > 0 {
+            continue;
+        }
+        if let Some(suggestion) = vocabulary.closest(&token) {
+            corrected = corrected.replacen(word, suggestion, 1);
+            changed = true;
+        }
+    }
+
+    changed.then_some(corrected)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
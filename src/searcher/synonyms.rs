@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use super::query::split_clauses;
+
+/// Query-time synonyms, e.g. `js -> javascript` or `ml -> machine learning`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SynonymMap(HashMap<String, Vec<String>>);
+
+impl SynonymMap {
+    /// Parses one `word -> synonym, other synonym` rule per line. Blank lines and `#`
+    /// comments are ignored; rules for the same word accumulate.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut map = Self::default();
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((word, synonyms)) = line.split_once("->") else {
+                return Err(format!("line {}: expected 'word -> synonym', got '{}'", n + 1, line));
+            };
+            let word = word.trim();
+            if word.is_empty() || word.contains(char::is_whitespace) {
+                return Err(format!("line {}: '{}' must be a single word", n + 1, word));
+            }
+            let synonyms = synonyms
+                .split(',')
+                .map(|s| s.trim().replace('"', ""))
+                .filter(|s| !s.is_empty());
+            map.insert(word, synonyms);
+        }
+        Ok(map)
+    }
+
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn insert(&mut self, word: &str, synonyms: impl IntoIterator<Item = String>) {
+        let entry = self.0.entry(word.to_lowercase()).or_default();
+        for synonym in synonyms {
+            let synonym = synonym.to_lowercase();
+            if !entry.contains(&synonym) {
+                entry.push(synonym);
+            }
+        }
+    }
+
+    pub fn get(&self, word: &str) -> Option<&[String]> {
+        self.0.get(&word.to_lowercase()).map(Vec::as_slice)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Rewrites the plain words of `query` that have synonyms into an OR group, keeping
+    /// any `+`/`-` operator: `+js tutorial` becomes `+(js OR javascript) tutorial`.
+    /// Multi-word synonyms become phrases. Phrases and field-scoped clauses are left
+    /// alone. The result goes through the query parser, so synonyms are analyzed
+    /// (lowercased, stemmed) exactly like the words they stand in for.
+    pub fn expand(&self, query: &str) -> String {
+        if self.is_empty() {
+            return query.to_string();
+        }
+        let clauses: Vec<String> = split_clauses(query)
+            .into_iter()
+            .map(|clause| {
+                let (operator, word) = match clause.strip_prefix(['+', '-']) {
+                    Some(rest) => clause.split_at(clause.len() - rest.len()),
+                    None => ("", clause),
+                };
+                let plain = !word.is_empty() && word.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+                match self.get(word).filter(|_| plain) {
+                    Some(synonyms) => {
+                        let alternatives: Vec<String> = synonyms
+                            .iter()
+                            .map(|s| if s.contains(char::is_whitespace) { format!("\"{}\"", s) } else { s.clone() })
+                            .collect();
+                        format!("{}({} OR {})", operator, word, alternatives.join(" OR "))
+                    }
+                    None => clause.to_string(),
+                }
+            })
+            .collect();
+        clauses.join(" ")
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::normalize::WordOptions;
+use super::Stopwords;
+use crate::crawler::datascraper::count_word_ids;
+
+/// Gives every distinct word a small integer id, so per-page counts can be kept as
+/// `(id, count)` pairs while each word's text is stored only once for the whole crawl.
+#[derive(Debug, Clone, Default)]
+pub struct WordInterner {
+    ids: HashMap<Arc<str>, u32>,
+    /// Indexed by id. Shares its strings with `ids`.
+    words: Vec<Arc<str>>,
+}
+
+impl WordInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `word`, assigning the next free one if it's new.
+    pub fn intern(&mut self, word: &str) -> u32 {
+        if let Some(&id) = self.ids.get(word) {
+            return id;
+        }
+        let id = u32::try_from(self.words.len()).expect("fewer than 2^32 distinct words");
+        let word: Arc<str> = Arc::from(word);
+        self.words.push(word.clone());
+        self.ids.insert(word, id);
+        id
+    }
+
+    /// The id of `word`, if it was interned.
+    pub fn id(&self, word: &str) -> Option<u32> {
+        self.ids.get(word).copied()
+    }
+
+    /// The word with this id.
+    pub fn word(&self, id: u32) -> Option<&str> {
+        self.words.get(id as usize).map(|word| &**word)
+    }
+
+    /// Number of distinct words.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// A [`WordInterner`] shared by a scraper and its clones, so every page of a crawl numbers
+/// its words alike and each word's text is stored once for the whole crawl.
+#[derive(Debug, Clone, Default)]
+pub struct SharedInterner(Arc<RwLock<WordInterner>>);
+
+impl SharedInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, WordInterner> {
+        self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, WordInterner> {
+        self.0.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether both are the same interner, so their ids mean the same words.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// One page's word frequencies as `(id, count)` pairs sorted by id, numbered by a
+/// [`SharedInterner`]. Serialized as a map from word to count.
+#[derive(Debug, Clone)]
+pub struct WordCounts {
+    interner: SharedInterner,
+    counts: Vec<(u32, u32)>,
+}
+
+impl WordCounts {
+    /// Counts the words of `text`, see [`count_word_ids`].
+    pub fn count(text: &str, stopwords: &Stopwords, options: WordOptions, interner: &SharedInterner) -> Self {
+        let counts = count_word_ids(text, stopwords, options, &mut interner.write());
+        Self { interner: interner.clone(), counts }
+    }
+
+    /// Counts keyed by word, e.g. from [`count_words`](crate::crawler::datascraper::count_words).
+    pub fn from_map(counts: &HashMap<String, u32>, interner: &SharedInterner) -> Self {
+        let mut words = interner.write();
+        let mut counts: Vec<(u32, u32)> = counts.iter().map(|(word, &count)| (words.intern(word), count)).collect();
+        counts.sort_unstable();
+        Self { interner: interner.clone(), counts }
+    }
+
+    /// Occurrences of `word`, if the page contains it.
+    pub fn get(&self, word: &str) -> Option<u32> {
+        let id = self.interner.read().id(word)?;
+        self.counts.binary_search_by_key(&id, |&(id, _)| id).ok().map(|i| self.counts[i].1)
+    }
+
+    /// The `(id, count)` pairs, sorted by id.
+    pub fn ids(&self) -> &[(u32, u32)] {
+        &self.counts
+    }
+
+    /// The interner the ids come from.
+    pub fn interner(&self) -> &SharedInterner {
+        &self.interner
+    }
+
+    /// Number of distinct words.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Counts keyed by word.
+    pub fn to_map(&self) -> HashMap<String, u32> {
+        let words = self.interner.read();
+        self.counts.iter().filter_map(|&(id, count)| Some((words.word(id)?.to_string(), count))).collect()
+    }
+}
+
+impl Serialize for WordCounts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let words = self.interner.read();
+        let mut map = serializer.serialize_map(Some(self.counts.len()))?;
+        for &(id, count) in &self.counts {
+            if let Some(word) = words.word(id) {
+                map.serialize_entry(word, &count)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Deserialized counts get an interner of their own, as the ids they had aren't known.
+impl<'de> Deserialize<'de> for WordCounts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts = HashMap::<String, u32>::deserialize(deserializer)?;
+        Ok(Self::from_map(&counts, &SharedInterner::new()))
+    }
+}
+
+/// Word frequencies summed over many pages, keyed by [`WordInterner`] id.
+///
+/// Built on the [`Scraper::word_interner`](crate::crawler::datascraper::Scraper::word_interner)
+/// of a crawl, pages' [`WordCounts`] are added by id without looking up their words again.
+#[derive(Debug, Clone, Default)]
+pub struct CorpusWordCounts {
+    interner: SharedInterner,
+    /// Occurrences over all pages, indexed by id.
+    totals: Vec<u64>,
+    /// Pages containing the word at least once, indexed by id.
+    pages: Vec<u32>,
+}
+
+impl CorpusWordCounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sums counts numbered by `interner`, e.g. a scraper's.
+    pub fn with_interner(interner: SharedInterner) -> Self {
+        Self { interner, ..Self::default() }
+    }
+
+    /// The interner ids of [`add_ids`](Self::add_ids) must come from.
+    pub fn interner(&self) -> &SharedInterner {
+        &self.interner
+    }
+
+    /// Adds one page's counts as returned by [`count_word_ids`], each id appearing once.
+    pub fn add_ids(&mut self, counts: &[(u32, u32)]) {
+        let len = self.interner.read().len();
+        self.totals.resize(len, 0);
+        self.pages.resize(len, 0);
+        for &(id, count) in counts {
+            self.totals[id as usize] += u64::from(count);
+            self.pages[id as usize] += 1;
+        }
+    }
+
+    /// Counts the words of one page's `text` straight into the totals and returns the
+    /// page's own `(id, count)` pairs, see [`count_word_ids`].
+    pub fn add_text(&mut self, text: &str, stopwords: &Stopwords, options: WordOptions) -> Vec<(u32, u32)> {
+        let counts = count_word_ids(text, stopwords, options, &mut self.interner.write());
+        self.add_ids(&counts);
+        counts
+    }
+
+    /// Adds one page's [`ScrapeResult::word_counts`](crate::crawler::datascraper::ScrapeResult::word_counts).
+    /// Counts numbered by another interner are added by word.
+    pub fn add_word_counts(&mut self, counts: &WordCounts) {
+        if counts.interner().ptr_eq(&self.interner) {
+            self.add_ids(counts.ids());
+            return;
+        }
+        let ids = WordCounts::from_map(&counts.to_map(), &self.interner);
+        self.add_ids(ids.ids());
+    }
+
+    /// Occurrences of `word` over all pages added so far.
+    pub fn total(&self, word: &str) -> u64 {
+        self.interner.read().id(word).and_then(|id| self.totals.get(id as usize)).copied().unwrap_or(0)
+    }
+
+    /// Pages added so far that contain `word`.
+    pub fn pages_containing(&self, word: &str) -> u32 {
+        self.interner.read().id(word).and_then(|id| self.pages.get(id as usize)).copied().unwrap_or(0)
+    }
+
+    /// The `n` most frequent words over all pages, most frequent first and ties broken
+    /// alphabetically, like [`ScrapeResult::top_terms`](crate::crawler::datascraper::ScrapeResult::top_terms).
+    pub fn top_terms(&self, n: usize) -> Vec<(String, u64)> {
+        let words = self.interner.read();
+        let mut terms: Vec<(&str, u64)> = self
+            .totals
+            .iter()
+            .enumerate()
+            .filter(|&(_, &total)| total > 0)
+            .filter_map(|(id, &total)| Some((words.word(id as u32)?, total)))
+            .collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        terms.truncate(n);
+        terms.into_iter().map(|(word, total)| (word.to_string(), total)).collect()
+    }
+}
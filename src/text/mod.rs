@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+pub mod interner;
+pub mod normalize;
+
+/// English stopwords, the same list Lucene's `EnglishAnalyzer` uses plus a few very common extras.
+pub const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has", "have", "if",
+    "in", "into", "is", "it", "its", "no", "not", "of", "on", "or", "such", "that", "the",
+    "their", "then", "there", "these", "they", "this", "to", "was", "were", "will", "with",
+];
+
+/// Words ignored when indexing text and counting terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stopwords(HashSet<String>);
+
+impl Default for Stopwords {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Stopwords {
+    pub fn english() -> Self {
+        Self::from_words(ENGLISH_STOPWORDS.iter().map(|w| w.to_string()))
+    }
+
+    /// An empty list, i.e. keep every word.
+    pub fn none() -> Self {
+        Self(HashSet::new())
+    }
+
+    pub fn from_words(words: impl IntoIterator<Item = String>) -> Self {
+        Self(words.into_iter().map(|w| w.to_lowercase()).collect())
+    }
+
+    /// Loads a stopword file: one word per line, blank lines and `#` comments are ignored.
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::from_words(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        ))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.0.contains(word)
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        self.0.iter()
+    }
+}
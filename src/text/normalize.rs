@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode normalization applied to text before it is split into words.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Leave the text as fetched (default).
+    #[default]
+    None,
+    /// Canonical composition: `e` + combining acute becomes `é`, so both spellings count
+    /// as the same word.
+    Nfc,
+    /// Compatibility composition: additionally folds ligatures (`ﬁ`), full-width forms
+    /// (`ｒｕｓｔ`) and similar variants into their plain equivalents.
+    Nfkc,
+}
+
+impl Normalization {
+    pub fn apply(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Normalization::None => Cow::Borrowed(text),
+            Normalization::Nfc => Cow::Owned(text.nfc().collect()),
+            Normalization::Nfkc => Cow::Owned(text.nfkc().collect()),
+        }
+    }
+}
+
+impl std::str::FromStr for Normalization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "nfc" => Ok(Self::Nfc),
+            "nfkc" => Ok(Self::Nfkc),
+            other => Err(format!("Unknown normalization '{}'. Use 'none', 'nfc' or 'nfkc'.", other)),
+        }
+    }
+}
+
+/// Which characters make up a word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WordMode {
+    /// Runs of letters and digits (default). Combining marks end a word, so decomposed
+    /// accents and scripts such as Devanagari get cut apart, and unspaced CJK text
+    /// becomes one long "word".
+    #[default]
+    Alphanumeric,
+    /// Words across scripts: combining marks stay part of the word they modify, and runs
+    /// of Chinese or Japanese characters, which aren't separated by spaces, are split
+    /// into overlapping two-character words.
+    Unicode,
+}
+
+/// How text is turned into words for [`count_words`](crate::crawler::datascraper::count_words).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WordOptions {
+    pub normalization: Normalization,
+    pub mode: WordMode,
+}
+
+/// Han ideographs and Japanese kana: scripts written without spaces between words.
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+        | '\u{31F0}'..='\u{31FF}'   // Katakana phonetic extensions
+        | '\u{3400}'..='\u{4DBF}'   // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK compatibility ideographs
+        | '\u{FF66}'..='\u{FF9F}'   // Half-width Katakana
+        | '\u{20000}'..='\u{2FA1F}' // CJK extensions B-F, compatibility supplement
+    )
+}
+
+/// Lowercase words of `text`. Internal hyphens are kept so technical terms like
+/// "covid-19" or "gpt-4" stay whole. Plain words need at least 3 characters, tokens
+/// containing a digit only 2 (e.g. "5g"); CJK words in [`WordMode::Unicode`] are exempt.
+pub fn words(text: &str, options: WordOptions) -> Vec<String> {
+    let mut words = Vec::new();
+    for_each_word(text, options, |word| words.push(word.to_string()));
+    words
+}
+
+/// Calls `f` with each of the [`words`] of `text` in turn, without collecting them. The
+/// word is only borrowed, so callers that already know it (e.g. a
+/// [`WordInterner`](super::interner::WordInterner)) don't allocate per occurrence.
+pub fn for_each_word(text: &str, options: WordOptions, mut f: impl FnMut(&str)) {
+    let text = options.normalization.apply(text);
+    let mut run = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+    let mut buffer = String::new();
+    let unicode = options.mode == WordMode::Unicode;
+    for c in text.chars() {
+        if unicode && is_cjk(c) {
+            flush_word(&mut run, &mut f);
+            cjk_run.push(c);
+            continue;
+        }
+        flush_cjk(&mut cjk_run, &mut buffer, &mut f);
+        if c.is_alphanumeric() || c == '-' || (unicode && is_combining_mark(c) && !run.is_empty()) {
+            run.push(c);
+        } else {
+            flush_word(&mut run, &mut f);
+        }
+    }
+    flush_word(&mut run, &mut f);
+    flush_cjk(&mut cjk_run, &mut buffer, &mut f);
+}
+
+fn flush_word(run: &mut String, f: &mut impl FnMut(&str)) {
+    // A run of hyphens ("foo--bar") is punctuation, not a compound word
+    for word in run.split("--").map(|word| word.trim_matches('-')) {
+        let min_len = if word.chars().any(|c| c.is_numeric()) { 2 } else { 3 };
+        if word.chars().count() >= min_len {
+            // Most words are lowercase already and can be passed on without a copy
+            if word.chars().all(|c| c.is_lowercase() || !c.is_alphabetic()) {
+                f(word);
+            } else {
+                f(&word.to_lowercase());
+            }
+        }
+    }
+    run.clear();
+}
+
+/// A single character stays a word of its own, longer runs become overlapping bigrams.
+/// `buffer` is reused for each of them.
+fn flush_cjk(run: &mut Vec<char>, buffer: &mut String, f: &mut impl FnMut(&str)) {
+    if run.len() == 1 {
+        buffer.clear();
+        buffer.push(run[0]);
+        f(buffer);
+    }
+    for pair in run.windows(2) {
+        buffer.clear();
+        buffer.extend(pair);
+        f(buffer);
+    }
+    run.clear();
+}
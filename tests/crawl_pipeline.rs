@@ -0,0 +1,732 @@
+//! Crawls a handful of interlinked pages served by a local mock server, indexes them in
+//! memory and searches the result.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use search_enginge::crawler::blocklist::{read_list, Blocklist};
+use search_enginge::crawler::datascraper::{LinkSource, ScrapeError, ScrapeResult, Scraper};
+use search_enginge::crawler::dump::{read_header, read_pages, DumpHeader, PageDump};
+use search_enginge::crawler::focus::{topic_score, TopicFocus};
+use search_enginge::crawler::language::{LanguageDetector, LanguageFilter};
+use search_enginge::crawler::stats::SkipReason;
+use search_enginge::crawler::Crawler;
+use search_enginge::indexer::meta::IndexMeta;
+use search_enginge::indexer::{index_dump, index_pages, run_dump_indexer, run_indexer, IndexBackend, IndexerConfig};
+use search_enginge::searcher::{SearchConfig, Searcher};
+use search_enginge::text::interner::CorpusWordCounts;
+use tantivy::schema::Value;
+use tantivy::TantivyDocument;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn html(title: &str, body: &str, links: &[&str]) -> String {
+    let links: String = links.iter().map(|href| format!("<a href=\"{}\">{}</a> ", href, href)).collect();
+    format!("<html><head><title>{}</title></head><body><p>{}</p>{}</body></html>", title, body, links)
+}
+
+/// Serves `page` at `route`, expecting it to be requested exactly once.
+async fn serve(server: &MockServer, route: &str, page: String) {
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html; charset=utf-8"))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+/// `/` links to every page and the pages link to each other and back, so every page is
+/// discovered several times.
+async fn mock_site() -> MockServer {
+    let server = MockServer::start().await;
+    serve(&server, "/", html(
+        "Home",
+        "Welcome to the test site about programming languages and gardening.",
+        &["/rust", "/python", "/garden"],
+    )).await;
+    serve(&server, "/rust", html(
+        "Rust ownership guide",
+        "Rust ownership explained. Ownership moves values, borrowing lends them. Ownership rules keep memory safe.",
+        &["/", "/python", "/garden"],
+    )).await;
+    serve(&server, "/python", html(
+        "Python basics",
+        "Python is dynamically typed. Unlike Rust it has no ownership, memory is garbage collected.",
+        &["/", "/rust"],
+    )).await;
+    serve(&server, "/garden", html(
+        "Gardening",
+        "Water tomatoes early in the morning and keep the soil moist during summer.",
+        &["/", "/rust#top"],
+    )).await;
+    server
+}
+
+fn top_urls(engine: &Searcher, query: &str) -> Vec<String> {
+    let searcher = engine.searcher();
+    let query = engine.parse_query(query).expect("query parses");
+    engine
+        .top_by_relevance(&searcher, query.as_ref(), &[], 10)
+        .expect("search succeeds")
+        .into_iter()
+        .map(|(_, address)| {
+            let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+            doc.get_first(engine.fields().url).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn crawls_indexes_and_ranks_a_small_site() {
+    let server = mock_site().await;
+    let base = server.uri();
+    let seed = format!("{}/", base);
+
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_progress(false);
+    let (pages, stats) = crawler.crawl(50, 4).await.expect("crawl succeeds");
+
+    // Every page was reached through links and fetched once, despite being linked from several pages
+    let mut urls: Vec<&str> = pages.iter().map(|p| p.url.as_str()).collect();
+    urls.sort_unstable();
+    let expected: Vec<String> = ["/", "/garden", "/python", "/rust"].iter().map(|p| format!("{}{}", base, p)).collect();
+    assert_eq!(urls, expected);
+    assert_eq!(stats.pages_fetched(), 4);
+    assert_eq!(stats.pages_skipped(), 0);
+
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(pages, &IndexBackend::Memory, &config).expect("indexing succeeds");
+    let engine = Searcher::from_index(index, &SearchConfig::default()).expect("searcher opens");
+
+    let ownership = top_urls(&engine, "ownership");
+    assert_eq!(ownership.first(), Some(&format!("{}/rust", base)));
+    assert_eq!(ownership.len(), 2);
+    assert_eq!(top_urls(&engine, "tomatoes"), vec![format!("{}/garden", base)]);
+    assert!(top_urls(&engine, "spaceship").is_empty());
+
+    // The `expect(1)` on each mock: nothing was fetched twice
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn slow_pages_are_aborted_after_the_request_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(html("Slow", "eventually", &[]), "text/html")
+                .set_delay(Duration::from_secs(10)),
+        )
+        .mount(&server)
+        .await;
+    let url = format!("{}/slow", server.uri());
+    let timeout = Duration::from_millis(300);
+    let scraper = || Scraper::builder().request_timeout(timeout).build().expect("scraper builds");
+
+    let started = Instant::now();
+    let error = scraper().scrape(&url).await.expect_err("slow page times out");
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+    assert!(matches!(error.downcast_ref::<ScrapeError>(), Some(ScrapeError::Timeout(t)) if *t == timeout));
+
+    // The crawler adds no timeout of its own and counts the page as timed out
+    let started = Instant::now();
+    let mut crawler = Crawler::new(&[url.as_str()]).with_scraper(scraper()).with_progress(false);
+    let (pages, stats) = crawler.crawl(5, 1).await.expect("crawl succeeds");
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+    assert!(pages.is_empty());
+    assert_eq!(stats.skipped(SkipReason::Timeout), 1);
+}
+
+#[tokio::test]
+async fn broken_html_does_not_stop_the_crawl() {
+    let server = MockServer::start().await;
+    let broken_pages: [(&str, Vec<u8>); 4] = [
+        ("/unclosed", b"<html><head><title>Unclosed</title><body><p>never closed <a href='/nested'>next <div><span>".to_vec()),
+        ("/nested", format!("{}deep text <a href=\"/binary\">more</a>", "<div><table><b>".repeat(5_000)).into_bytes()),
+        ("/binary", [b"<html><body>\xff\xfe\x00\x80 <a href=\"/garbage\">go</a> \xc3\x28".as_slice(), &[0u8; 64]].concat()),
+        ("/garbage", b"</html></body><<<>>><a href=><a href='/good'>ok</a><!-- unterminated <script>var x = '</scr".to_vec()),
+    ];
+    for (route, body) in broken_pages {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/html"))
+            .mount(&server)
+            .await;
+    }
+    serve(&server, "/good", html("Good page", "well formed content", &[])).await;
+
+    let seed = format!("{}/unclosed", server.uri());
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_progress(false);
+    let (pages, stats) = crawler.crawl(10, 2).await.expect("crawl succeeds");
+
+    // Each broken page still yields its links, so the crawl reaches the well-formed page at the end
+    assert_eq!(stats.pages_fetched(), 5);
+    assert_eq!(stats.pages_skipped(), 0);
+    let good = pages.iter().find(|p| p.url.ends_with("/good")).expect("good page crawled");
+    assert_eq!(good.title.as_deref(), Some("Good page"));
+}
+
+#[tokio::test]
+async fn request_rate_is_capped_across_concurrent_fetches() {
+    let server = mock_site().await;
+    let seed = format!("{}/", server.uri());
+
+    // Four pages at 10 requests per second: the first goes out at once, the others 100ms apart
+    let started = Instant::now();
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_max_requests_per_second(Some(10.0)).with_progress(false);
+    let (pages, _) = crawler.crawl(50, 4).await.expect("crawl succeeds");
+
+    assert_eq!(pages.len(), 4);
+    assert!(started.elapsed() >= Duration::from_millis(300), "took {:?}", started.elapsed());
+    server.verify().await;
+}
+
+/// Part `n` of a three-part article series, linked only through `<link rel>` pagination.
+fn series_page(n: usize) -> String {
+    let mut head = format!("<title>Series part {}</title>", n);
+    if n > 1 {
+        head.push_str(&format!("<link rel=\"prev\" href=\"/part{}\">", n - 1));
+    }
+    if n < 3 {
+        head.push_str(&format!("<link rel=\"next\" href=\"/part{}\">", n + 1));
+    }
+    format!("<html><head>{}</head><body><p>Part {} of the series.</p></body></html>", head, n)
+}
+
+#[tokio::test]
+async fn pagination_links_are_only_followed_when_enabled() {
+    let server = MockServer::start().await;
+    for n in 1..=3 {
+        Mock::given(method("GET"))
+            .and(path(format!("/part{}", n)))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(series_page(n), "text/html"))
+            .mount(&server)
+            .await;
+    }
+    let middle = format!("{}/part2", server.uri());
+
+    let page = Scraper::new().scrape(&middle).await.expect("page scrapes");
+    assert!(page.links.is_empty());
+
+    let scraper = Scraper::builder().link_sources(vec![LinkSource::Pagination]).build().expect("scraper builds");
+    let page = scraper.scrape(&middle).await.expect("page scrapes");
+    assert_eq!(page.links, vec![format!("{}/part1", server.uri()), format!("{}/part3", server.uri())]);
+    assert!(page.anchors.is_empty());
+
+    let mut crawler = Crawler::new(&[middle.as_str()]).with_scraper(scraper).with_progress(false);
+    let (pages, _) = crawler.crawl(10, 2).await.expect("crawl succeeds");
+    assert_eq!(pages.len(), 3);
+}
+
+#[tokio::test]
+async fn focused_crawls_skip_links_from_off_topic_pages() {
+    let server = MockServer::start().await;
+    serve(&server, "/", html("Home", "Programming and gardening notes.", &["/rust", "/garden"])).await;
+    serve(&server, "/rust", html("Rust", "Ownership and borrowing in Rust programs.", &["/rust/traits"])).await;
+    serve(&server, "/garden", html(
+        "Gardening",
+        "Tomatoes need sunny soil. Water tomatoes early and mulch the soil.",
+        &["/garden/compost"],
+    )).await;
+    serve(&server, "/garden/compost", html("Compost", "Compost feeds the soil.", &[])).await;
+    Mock::given(method("GET"))
+        .and(path("/rust/traits"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(html("Traits", "Rust traits.", &[]), "text/html"))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let keywords = vec!["tomatoes".to_string(), "Soil".to_string()];
+    let seed = format!("{}/", server.uri());
+    let mut crawler = Crawler::new(&[seed.as_str()])
+        .with_focus(Some(TopicFocus::new(keywords.clone())))
+        .with_progress(false);
+    let (pages, _) = crawler.crawl(10, 3).await.expect("crawl succeeds");
+
+    let score = |route: &str| {
+        let page = pages.iter().find(|p| p.url == format!("{}{}", server.uri(), route)).expect("page was crawled");
+        topic_score(page, &keywords)
+    };
+    assert_eq!(pages.len(), 4);
+    assert_eq!(score("/rust"), 0.0);
+    assert!(score("/garden") > score("/garden/compost"));
+    assert!(score("/garden") <= 1.0);
+}
+
+#[tokio::test]
+async fn short_focus_keywords_are_matched() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ai"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            html("AI notes", "How AI models learn, and where ML and Go fit in. Tomatoes are off-topic.", &[]),
+            "text/html",
+        ))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let url = format!("{}/ai", server.uri());
+    let keywords = vec!["AI".to_string(), "ml".to_string()];
+
+    let page = Scraper::new().scrape(&url).await.expect("page scrapes");
+    let score = topic_score(&page, &keywords);
+    assert!(score > 0.0);
+    assert_eq!(topic_score(&page, &["java".to_string()]), 0.0);
+    // Also when scoring the word counts of the scraper, which leave such words out
+    let counted = Scraper::builder().count_words(true).build().expect("scraper builds").scrape(&url).await.expect("page scrapes");
+    assert!(counted.word_counts.as_ref().is_some_and(|counts| counts.get("ai").is_none()));
+    assert!(topic_score(&counted, &keywords) > 0.0);
+}
+
+#[tokio::test]
+async fn crawled_word_counts_share_the_scrapers_interner() {
+    let server = mock_site().await;
+    let seed = format!("{}/", server.uri());
+    let scraper = Scraper::builder().count_words(true).build().expect("scraper builds");
+    let interner = scraper.word_interner().clone();
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_scraper(scraper).with_progress(false);
+    let (pages, _) = crawler.crawl(50, 4).await.expect("crawl succeeds");
+    assert_eq!(pages.len(), 4);
+
+    let mut corpus = CorpusWordCounts::with_interner(interner.clone());
+    let mut distinct = HashSet::new();
+    for page in &pages {
+        let counts = page.word_counts.as_ref().expect("words are counted");
+        assert!(counts.interner().ptr_eq(&interner));
+        distinct.extend(counts.to_map().into_keys());
+        corpus.add_word_counts(counts);
+    }
+    // Each word is stored once for the crawl, not once per page using it
+    assert_eq!(interner.read().len(), distinct.len());
+    let per_page: u64 = pages.iter().filter_map(|p| p.word_counts.as_ref()?.get("ownership")).map(u64::from).sum();
+    assert!(per_page >= 4);
+    assert_eq!(corpus.total("ownership"), per_page);
+    assert_eq!(corpus.pages_containing("ownership"), 2);
+
+    // Dumps keep the words, not the ids
+    let json = serde_json::to_value(&pages[0]).expect("page serializes");
+    let reread: ScrapeResult = serde_json::from_value(json.clone()).expect("page deserializes");
+    assert!(json["word_counts"].is_object());
+    assert_eq!(reread.word_counts.map(|c| c.to_map()), pages[0].word_counts.as_ref().map(|c| c.to_map()));
+}
+
+fn redirect(to: &str) -> ResponseTemplate {
+    ResponseTemplate::new(301).insert_header("Location", to)
+}
+
+#[tokio::test]
+async fn redirect_chains_are_recorded_and_searchable() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/old")).respond_with(redirect("/moved")).mount(&server).await;
+    Mock::given(method("GET")).and(path("/moved")).respond_with(redirect("/new")).mount(&server).await;
+    Mock::given(method("GET"))
+        .and(path("/new"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(html("New", "Tomatoes moved here.", &[]), "text/html"))
+        .mount(&server)
+        .await;
+    let old = format!("{}/old", server.uri());
+
+    let page = Scraper::new().scrape(&old).await.expect("page scrapes");
+    assert_eq!(page.status, Some(200));
+    assert_eq!(page.redirected_from, None);
+
+    let scraper = Scraper::builder().record_redirects(true).build().expect("scraper builds");
+    let page = scraper.scrape(&old).await.expect("page scrapes");
+    assert_eq!(page.url, format!("{}/new", server.uri()));
+    assert_eq!(page.redirected_from, Some(vec![old.clone(), format!("{}/moved", server.uri())]));
+    let direct = scraper.scrape(&page.url).await.expect("page scrapes");
+    assert_eq!(direct.redirected_from, Some(Vec::new()));
+
+    let mut other = direct;
+    other.url = format!("{}/other", server.uri());
+    other.body_text = "Tomatoes without a detour.".to_string();
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(vec![page, other], &IndexBackend::Memory, &config).expect("indexing succeeds");
+    let engine = Searcher::from_index(index, &SearchConfig::default()).expect("searcher opens");
+
+    assert_eq!(top_urls(&engine, "tomatoes redirects:>0"), vec![format!("{}/new", server.uri())]);
+    assert_eq!(top_urls(&engine, "tomatoes status:200").len(), 2);
+    assert!(top_urls(&engine, "tomatoes status:[300 TO 399]").is_empty());
+}
+
+#[tokio::test]
+async fn links_outside_the_path_prefix_are_not_followed() {
+    let server = MockServer::start().await;
+    serve(&server, "/docs/", html("Docs", "Documentation index.", &["/docs/intro", "/docsearch", "/blog/post"])).await;
+    serve(&server, "/docs/intro", html("Intro", "Getting started.", &["/docs/", "/about"])).await;
+    for outside in ["/docsearch", "/blog/post", "/about"] {
+        Mock::given(method("GET"))
+            .and(path(outside))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(html("Outside", "Not documentation.", &[]), "text/html"))
+            .expect(0)
+            .mount(&server)
+            .await;
+    }
+
+    let seed = format!("{}/docs/", server.uri());
+    // Scheme and host of a prefix are matched case-insensitively, the path isn't
+    let prefix = seed.replacen("http", "HTTP", 1);
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_path_prefixes(vec![prefix]).with_progress(false);
+    let (pages, _) = crawler.crawl(10, 3).await.expect("crawl succeeds");
+
+    let mut urls: Vec<&str> = pages.iter().map(|p| p.url.as_str()).collect();
+    urls.sort_unstable();
+    assert_eq!(urls, vec![seed.clone(), format!("{}/docs/intro", server.uri())]);
+}
+
+#[tokio::test]
+async fn language_is_detected_on_the_main_content() {
+    let chrome = "Home About us Contact Subscribe to our newsletter for the latest news and updates. \
+        Read more stories from around the world, follow us on social media and share this page with your friends. \
+        Privacy policy Terms of service Cookie settings Careers Advertise with us Help center and frequently asked questions.";
+    let article = "Le gouvernement a présenté mercredi un nouveau projet de loi sur le logement, \
+        qui prévoit la construction de milliers d'appartements dans les grandes villes du pays.";
+    let page = format!(
+        "<html><body><nav>{0}</nav><main><article><p>{1}</p></article></main><aside>{0}</aside><footer>{0}</footer></body></html>",
+        chrome, article
+    );
+    let server = MockServer::start().await;
+    serve(&server, "/article", page).await;
+
+    let page = Scraper::new().scrape(&format!("{}/article", server.uri())).await.expect("page scrapes");
+    // The whole text reads as English, the article it's about is French
+    assert_eq!(LanguageDetector::default().detect(&page.body_text), "eng");
+    assert_eq!(page.language, "fra");
+}
+
+#[tokio::test]
+async fn gzip_encoded_pages_are_decoded() {
+    let body = "Compressed pages are decoded before parsing, so their text is searchable like any other.";
+    let page = html("Gzip page", body, &[]);
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(page.as_bytes()).expect("page compresses");
+    let compressed = encoder.finish().expect("page compresses");
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/gzip"))
+        .and(wiremock::matchers::header_regex("accept-encoding", "gzip"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .set_body_raw(compressed, "text/html; charset=utf-8"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let page = Scraper::new().scrape(&format!("{}/gzip", server.uri())).await.expect("page scrapes");
+    assert_eq!(page.title.as_deref(), Some("Gzip page"));
+    assert_eq!(page.body_text, body);
+    assert_eq!(page.language, "eng");
+}
+
+#[tokio::test]
+async fn rate_limited_pages_are_retried_after_retry_after() {
+    let server = MockServer::start().await;
+    // Mocks are tried in mount order: the first request is turned away, later ones get the page
+    Mock::given(method("GET"))
+        .and(path("/busy"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    serve(&server, "/busy", html("Busy", "Served once the server had time.", &[])).await;
+
+    let seed = format!("{}/busy", server.uri());
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_progress(false);
+    let started = Instant::now();
+    let (pages, stats) = crawler.crawl(5, 2).await.expect("crawl succeeds");
+
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].title.as_deref(), Some("Busy"));
+    assert_eq!(stats.skipped(SkipReason::ClientError), 0);
+    assert!(started.elapsed() >= Duration::from_secs(1), "retried before Retry-After passed");
+}
+
+#[tokio::test]
+async fn dumped_crawls_index_like_collected_ones() {
+    let server = mock_site().await;
+    let base = server.uri();
+    let seed = format!("{}/", base);
+    let dump_path = std::env::temp_dir().join(format!("search_enginge_dump_{}.ndjson", std::process::id()));
+
+    let header = DumpHeader { seeds: vec![seed.clone()], crawled_at: 1_700_000_000 };
+    let dump = PageDump::create(&dump_path, &header).expect("dump file is created");
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_page_dump(Some(dump)).with_progress(false);
+    let (pages, stats) = crawler.crawl(50, 4).await.expect("crawl succeeds");
+    assert!(pages.is_empty(), "pages went to the dump only");
+    assert_eq!(read_header(&dump_path).expect("dump opens"), Some(header));
+    assert_eq!(stats.pages_fetched(), 4);
+
+    let dumped: Vec<_> = read_pages(&dump_path).expect("dump opens").collect::<Result<_, _>>().expect("every line is a page");
+    assert_eq!(dumped.len(), 4);
+
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let from_dump = index_dump(&dump_path, &IndexBackend::Memory, &config).expect("dump indexes");
+    let from_pages = index_pages(dumped, &IndexBackend::Memory, &config).expect("pages index");
+    std::fs::remove_file(&dump_path).ok();
+
+    let from_dump = Searcher::from_index(from_dump, &SearchConfig::default()).expect("searcher opens");
+    let from_pages = Searcher::from_index(from_pages, &SearchConfig::default()).expect("searcher opens");
+    assert_eq!(from_dump.searcher().num_docs(), 4);
+    for query in ["ownership", "tomatoes", "welcome"] {
+        assert_eq!(top_urls(&from_dump, query), top_urls(&from_pages, query), "query '{}'", query);
+    }
+    assert_eq!(top_urls(&from_dump, "ownership").first(), Some(&format!("{}/rust", base)));
+}
+
+#[tokio::test]
+async fn rebuilding_from_a_dump_records_the_crawl_that_wrote_it() {
+    let server = mock_site().await;
+    let seed = format!("{}/", server.uri());
+    let dump_path = std::env::temp_dir().join(format!("search_enginge_rebuild_{}.ndjson", std::process::id()));
+    let dir = std::env::temp_dir().join(format!("search_enginge_rebuild_{}", std::process::id()));
+
+    let header = DumpHeader { seeds: vec![seed.clone()], crawled_at: 1_700_000_000 };
+    let dump = PageDump::create(&dump_path, &header).expect("dump file is created");
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_page_dump(Some(dump)).with_progress(false);
+    crawler.crawl(50, 4).await.expect("crawl succeeds");
+    let fetched_at: std::collections::HashMap<_, _> = read_pages(&dump_path)
+        .expect("dump opens")
+        .map(|page| page.map(|page| (page.url, page.fetched_at.expect("fetched pages record their fetch time"))))
+        .collect::<Result<_, _>>()
+        .expect("every line is a page");
+
+    // The default config's seeds are not the crawl's
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    run_dump_indexer(dir.to_str().expect("UTF-8 path"), dump_path.to_str().expect("UTF-8 path"), &config);
+    std::fs::remove_file(&dump_path).ok();
+
+    let meta = IndexMeta::read(&dir).expect("metadata is read").expect("metadata is written");
+    assert_eq!(meta.seeds, vec![seed]);
+    assert_eq!(meta.crawled_at, 1_700_000_000);
+
+    let engine = Searcher::open(dir.to_str().expect("UTF-8 path"), &SearchConfig::default()).expect("searcher opens");
+    let searcher = engine.searcher();
+    let addresses = searcher.search(&tantivy::query::AllQuery, &tantivy::collector::DocSetCollector).expect("search succeeds");
+    assert_eq!(addresses.len(), 4);
+    for address in addresses {
+        let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+        let url = doc.get_first(engine.fields().url).and_then(|v| v.as_str()).expect("url is stored");
+        let crawled_at = doc.get_first(engine.fields().crawled_at).and_then(|v| v.as_i64()).expect("crawled_at is stored");
+        assert_eq!(Some(&crawled_at), fetched_at.get(url), "{}", url);
+    }
+    drop(engine);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[tokio::test]
+async fn pages_outside_the_language_allowlist_are_skipped_but_followed() {
+    let server = MockServer::start().await;
+    serve(&server, "/", html(
+        "Home",
+        "Welcome to our website. Here you will find the latest news about travel, food and culture from around the world.",
+        &["/fr"],
+    )).await;
+    serve(&server, "/fr", html(
+        "Accueil",
+        "Bienvenue sur notre site. Vous trouverez ici les dernières nouvelles sur les voyages, la cuisine et la culture du monde entier.",
+        &["/guide"],
+    )).await;
+    serve(&server, "/guide", html(
+        "Guide",
+        "This travel guide explains how to plan a trip on a small budget and which cities are worth visiting in the spring.",
+        &[],
+    )).await;
+
+    let filter = LanguageFilter::new(&["en"], true).expect("'en' is a known language");
+    let seed = format!("{}/", server.uri());
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_language_filter(Some(filter)).with_progress(false);
+    let (pages, stats) = crawler.crawl(10, 2).await.expect("crawl succeeds");
+
+    let mut urls: Vec<&str> = pages.iter().map(|p| p.url.as_str()).collect();
+    urls.sort_unstable();
+    // The English guide is only linked from the French page
+    assert_eq!(urls, vec![seed.clone(), format!("{}/guide", server.uri())]);
+    assert_eq!(stats.skipped(SkipReason::Language), 1);
+    assert!(LanguageFilter::new(&["english"], true).is_err());
+}
+
+#[tokio::test]
+async fn blocked_domains_and_terms_are_kept_out_of_the_crawl() {
+    let server = MockServer::start().await;
+    let port = server.address().port();
+    // `localhost` reaches the same mock server under another host name
+    let blocked = format!("http://localhost:{}", port);
+    serve(&server, "/", html("Home", "Links to all kinds of places.", &[&format!("{}/linked", blocked), "/casino", "/ok"])).await;
+    serve(&server, "/casino", html("Luck", "Play at our ONLINE\ncasino tonight.", &["/behind-casino"])).await;
+    serve(&server, "/ok", html("Fine", "A perfectly ordinary page.", &[])).await;
+    for never in ["/linked", "/seed", "/behind-casino"] {
+        Mock::given(method("GET")).and(path(never)).respond_with(ResponseTemplate::new(200)).expect(0).mount(&server).await;
+    }
+
+    let list = std::env::temp_dir().join(format!("search_enginge_blocked_{}.txt", std::process::id()));
+    std::fs::write(&list, "# ads and trackers\n0.0.0.0 localhost\n\nads.example  # inline comment\n").expect("list is written");
+    let domains = read_list(&list).expect("list is read");
+    std::fs::remove_file(&list).ok();
+    assert_eq!(domains, vec!["localhost", "ads.example"]);
+
+    let blocklist = Blocklist::default().with_domains(&domains).with_terms(&["online casino"]);
+    assert!(blocklist.blocks_url("https://cdn.ads.example/pixel.gif"));
+    assert!(!blocklist.blocks_url("https://notads.example/"));
+
+    let seed = format!("{}/", server.uri());
+    let blocked_seed = format!("{}/seed", blocked);
+    let mut crawler = Crawler::new(&[seed.as_str(), blocked_seed.as_str()]).with_blocklist(blocklist).with_progress(false);
+    let (pages, stats) = crawler.crawl(10, 2).await.expect("crawl succeeds");
+
+    let mut urls: Vec<&str> = pages.iter().map(|p| p.url.as_str()).collect();
+    urls.sort_unstable();
+    assert_eq!(urls, vec![seed.clone(), format!("{}/ok", server.uri())]);
+    // The blocked seed and the page with the blocked term
+    assert_eq!(stats.skipped(SkipReason::Blocked), 2);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn the_per_domain_cap_counts_www_and_the_bare_host_as_one_site() {
+    // The mock server acts as a proxy, so it answers for any host name
+    let server = MockServer::start().await;
+    let links = ["http://www.site.test/1", "http://site.test/2", "http://www.site.test/3", "http://other.test/4"];
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(html("Home", "The home page.", &links), "text/html"))
+        .mount(&server)
+        .await;
+    for route in ["/1", "/2", "/3"] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(html("Page", "Another page.", &[]), "text/html"))
+            .mount(&server)
+            .await;
+    }
+    // Links again to a page already crawled and one already skipped for the cap
+    Mock::given(method("GET"))
+        .and(path("/4"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(html("Page", "Another page.", &["http://site.test/", "http://www.site.test/3"]), "text/html"))
+        .mount(&server)
+        .await;
+
+    let scraper = Scraper::with_proxy(&server.uri()).expect("scraper builds");
+    let mut crawler = Crawler::new(&["http://site.test/"])
+        .with_scraper(scraper)
+        .with_max_pages_per_domain(Some(2))
+        .with_progress(false);
+    let (pages, stats) = crawler.crawl(10, 1).await.expect("crawl succeeds");
+
+    let sites: Vec<&str> = pages.iter().filter_map(|p| p.url.split('/').nth(2)).collect();
+    assert_eq!(sites.iter().filter(|s| s.ends_with("site.test")).count(), 2, "{:?}", sites);
+    assert!(sites.contains(&"other.test"));
+    assert_eq!(stats.skipped(SkipReason::DomainLimit), 2);
+}
+
+#[cfg(feature = "pdf")]
+#[tokio::test]
+async fn linked_pdfs_are_crawled_for_their_text_and_title() {
+    let server = MockServer::start().await;
+    serve(&server, "/", html("Papers", "Our latest paper.", &["/paper.pdf"])).await;
+    Mock::given(method("GET"))
+        .and(path("/paper.pdf"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(include_bytes!("fixtures/sample.pdf").to_vec(), "application/pdf"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let seed = format!("{}/", server.uri());
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_progress(false);
+    let (pages, _) = crawler.crawl(10, 2).await.expect("crawl succeeds");
+
+    let pdf = pages.iter().find(|p| p.url.ends_with("/paper.pdf")).expect("the PDF is crawled");
+    assert_eq!(pdf.title.as_deref(), Some("Sample PDF"));
+    assert!(pdf.body_text.contains("Portable documents are indexed like web pages."), "{:?}", pdf.body_text);
+    assert!(pdf.links.is_empty());
+    assert_eq!(pdf.status, Some(200));
+}
+
+#[tokio::test]
+async fn non_http_and_binary_links_are_not_followed() {
+    let skipped_schemes = [
+        "mailto:someone@example.test",
+        "tel:+15551234567",
+        "data:text/html,<p>hi</p>",
+        "ftp://files.example.test/readme.txt",
+        "javascript:void(0)",
+    ];
+    let binary = ["/photo.jpg", "/diagram.PNG", "/release.zip", "/clip.mp4?autoplay=1"];
+    let kept = ["/guide", "/photos.html", "https://other.test/page"];
+    let server = MockServer::start().await;
+    let page = html("Links", "A page with all sorts of links.", &[&skipped_schemes[..], &binary[..], &kept[..]].concat());
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let url = format!("{}/", server.uri());
+    let resolve = |links: &[&str]| -> Vec<String> {
+        links.iter().map(|link| if link.starts_with('/') { format!("{}{}", server.uri(), link) } else { link.to_string() }).collect()
+    };
+
+    let links = Scraper::new().scrape(&url).await.expect("page scrapes").links;
+    assert_eq!(links, resolve(&kept));
+
+    // Binary assets are kept on request; other schemes never are
+    let scraper = Scraper::builder().include_binary_links(true).build().expect("scraper builds");
+    let links = scraper.scrape(&url).await.expect("page scrapes").links;
+    assert_eq!(links, resolve(&[&binary[..], &kept[..]].concat()));
+}
+
+#[tokio::test]
+async fn pages_not_found_served_with_200_are_kept_out_of_the_index() {
+    let server = MockServer::start().await;
+    serve(&server, "/", html("Home", "Guides about tomatoes and other vegetables.", &["/tomatoes", "/peppers"])).await;
+    serve(&server, "/tomatoes", html("Tomatoes", "Water tomatoes early in the morning.", &["/"])).await;
+    serve(&server, "/peppers", html("Page not found", "Sorry, we couldn't find any tomatoes or peppers here.", &["/"])).await;
+
+    let seed = format!("{}/", server.uri());
+    let mut crawler = Crawler::new(&[seed.as_str()]).with_progress(false);
+    let (pages, _) = crawler.crawl(10, 2).await.expect("crawl succeeds");
+    assert!(pages.iter().any(|p| p.url.ends_with("/peppers") && p.likely_404));
+
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(pages, &IndexBackend::Memory, &config).expect("indexing succeeds");
+    let engine = Searcher::from_index(index, &SearchConfig::default()).expect("searcher opens");
+    assert_eq!(engine.num_docs(), 2);
+    let mut tomatoes = top_urls(&engine, "tomatoes");
+    tomatoes.sort_unstable();
+    assert_eq!(tomatoes, vec![seed.clone(), format!("{}/tomatoes", server.uri())]);
+}
+
+#[tokio::test]
+async fn recrawls_record_the_indexed_urls_among_their_seeds() {
+    let server = MockServer::start().await;
+    serve(&server, "/", html("Home", "The new home page.", &[])).await;
+    // Fetched by the first crawl and refreshed by the second
+    Mock::given(method("GET"))
+        .and(path("/old"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(html("Old", "A page only the index still knows about.", &[]), "text/html"))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let seed = format!("{}/", server.uri());
+    let old = format!("{}/old", server.uri());
+
+    let dir = std::env::temp_dir().join(format!("search_enginge_recrawl_{}", std::process::id()));
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let mut crawler = Crawler::new(&[old.as_str()]).with_progress(false);
+    let (pages, _) = crawler.crawl(1, 1).await.expect("crawl succeeds");
+    index_pages(pages, &IndexBackend::Disk(dir.clone()), &config).expect("indexing succeeds");
+
+    let mut config = IndexerConfig { append_seeds_from_index: true, ..config };
+    config.crawl.seeds = vec![seed.clone()];
+    config.crawl.limit = 10;
+    run_indexer(dir.to_str().expect("UTF-8 path"), &config).await.expect("the crawl isn't cancelled");
+
+    let meta = IndexMeta::read(&dir).expect("metadata is read").expect("metadata is written");
+    assert_eq!(meta.seeds, vec![seed, old]);
+    assert_eq!(meta.documents_indexed, 2);
+    server.verify().await;
+    std::fs::remove_dir_all(&dir).ok();
+}
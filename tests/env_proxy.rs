@@ -0,0 +1,40 @@
+//! Kept in its own test binary: it sets `HTTP_PROXY` for the whole process.
+
+use search_enginge::crawler::datascraper::Scraper;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn page_server(title: &str) -> MockServer {
+    let server = MockServer::start().await;
+    let page = format!("<html><head><title>{}</title></head><body><p>Hello.</p></body></html>", title);
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn env_proxy_is_used_unless_turned_off() {
+    let proxy = page_server("Through the proxy").await;
+    let target = page_server("Direct").await;
+    // SAFETY: the only test in this binary, so nothing else changes the environment meanwhile
+    unsafe {
+        std::env::set_var("HTTP_PROXY", proxy.uri());
+        std::env::remove_var("NO_PROXY");
+        std::env::remove_var("no_proxy");
+    }
+    let url = format!("{}/page", target.uri());
+
+    let page = Scraper::builder().build().expect("scraper builds").scrape(&url).await.expect("page scrapes");
+    assert_eq!(page.title.as_deref(), Some("Through the proxy"));
+
+    let direct = Scraper::builder().respect_env_proxy(false).build().expect("scraper builds");
+    let page = direct.scrape(&url).await.expect("page scrapes");
+    assert_eq!(page.title.as_deref(), Some("Direct"));
+
+    // NO_PROXY exempts the target's host
+    unsafe { std::env::set_var("NO_PROXY", "127.0.0.1") };
+    let page = Scraper::builder().build().expect("scraper builds").scrape(&url).await.expect("page scrapes");
+    assert_eq!(page.title.as_deref(), Some("Direct"));
+}
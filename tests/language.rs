@@ -0,0 +1,41 @@
+use search_enginge::crawler::language::{LanguageDetector, UNKNOWN};
+
+const ENGLISH: &str = "The quick brown fox jumps over the lazy dog while the farmer watches from the porch \
+    and wonders whether the rain will come before the harvest is finished this year.";
+const GERMAN: &str = "Der schnelle braune Fuchs springt über den faulen Hund, während der Bauer von der \
+    Veranda aus zusieht und sich fragt, ob der Regen vor dem Ende der Ernte kommt.";
+
+#[test]
+fn detects_clear_text() {
+    let detector = LanguageDetector::default();
+    assert_eq!(detector.detect(ENGLISH), "eng");
+    assert_eq!(detector.detect(GERMAN), "deu");
+}
+
+#[test]
+fn short_ambiguous_text_is_unknown() {
+    let detector = LanguageDetector::default();
+    for text in ["ok", "Menu", "Login", "OK 2024", "taxi hotel", "", "12345"] {
+        assert_eq!(detector.detect(text), UNKNOWN, "{:?} should be unknown", text);
+    }
+}
+
+#[test]
+fn zero_confidence_accepts_any_guess() {
+    let detector = LanguageDetector::default().with_min_confidence(0.0);
+    assert_ne!(detector.detect("Menu"), UNKNOWN);
+}
+
+#[test]
+fn expected_languages_restrict_the_guess() {
+    let detector = LanguageDetector::default().with_expected_languages(&["eng", "deu"]).unwrap();
+    assert_eq!(detector.detect(GERMAN), "deu");
+    // Spanish isn't a candidate, so it can't be guessed
+    let guess = detector.detect("El rápido zorro marrón salta sobre el perro perezoso mientras el granjero mira.");
+    assert_ne!(guess, "spa");
+}
+
+#[test]
+fn rejects_unknown_language_codes() {
+    assert!(LanguageDetector::default().with_expected_languages(&["english"]).is_err());
+}
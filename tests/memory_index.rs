@@ -0,0 +1,569 @@
+use search_enginge::crawler::datascraper::ScrapeResult;
+use search_enginge::crawler::jsonld::StructuredData;
+use search_enginge::indexer::maintenance::{compact, IndexUsage};
+use search_enginge::indexer::recrawl::{merge_seeds, stored_urls};
+use search_enginge::indexer::{index_pages, IndexBackend, IndexerConfig};
+use search_enginge::searcher::bm25::Bm25Params;
+use search_enginge::searcher::diversity::{cap_per_domain, dedup_by_url, restrict_to_domain};
+use search_enginge::searcher::matched::query_words;
+use search_enginge::searcher::projection::Projection;
+use search_enginge::searcher::query::validate_query;
+use search_enginge::searcher::{SearchConfig, Searcher};
+use search_enginge::indexer::schema::WebpageSchema;
+use search_enginge::text::Stopwords;
+use tantivy::schema::{Schema, Value};
+use tantivy::{doc, Index, TantivyDocument};
+
+fn page(url: &str, title: &str, body: &str, links: &[&str]) -> ScrapeResult {
+    ScrapeResult {
+        url: url.to_string(),
+        title: Some(title.to_string()),
+        body_text: body.to_string(),
+        links: links.iter().map(|l| l.to_string()).collect(),
+        internal_links: 0,
+        external_links: 0,
+        anchors: Vec::new(),
+        is_partial: false,
+        language: "eng".to_string(),
+        word_counts: None,
+        structured: StructuredData::default(),
+        no_index: false,
+        no_follow: false,
+        likely_404: false,
+        bytes_downloaded: 0,
+        favicon: None,
+        status: None,
+        redirected_from: None,
+        fetched_at: None,
+    }
+}
+
+fn memory_searcher(pages: Vec<ScrapeResult>) -> Searcher {
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(pages, &IndexBackend::Memory, &config).expect("in-memory indexing succeeds");
+    Searcher::from_index(index, &SearchConfig::default()).expect("searcher opens")
+}
+
+fn top_urls(engine: &Searcher, query: &str) -> Vec<String> {
+    let searcher = engine.searcher();
+    let mut analyzer = engine.index().tokenizers().get("en_stem").expect("tokenizer registered");
+    let words = query_words(&mut analyzer, query);
+    let query = engine.parse_query(query).expect("query parses");
+    engine
+        .top_by_relevance(&searcher, query.as_ref(), &words, 10)
+        .expect("search succeeds")
+        .into_iter()
+        .map(|(_, address)| {
+            let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+            doc.get_first(engine.fields().url).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn indexes_and_queries_in_memory() {
+    let engine = memory_searcher(vec![
+        page("https://a.test/", "Ownership in Rust", "ownership and borrowing rules of rust", &["https://b.test/"]),
+        page("https://b.test/", "Garden tips", "watering tomatoes in summer", &["https://a.test/"]),
+    ]);
+
+    assert_eq!(top_urls(&engine, "ownership"), vec!["https://a.test/"]);
+    assert_eq!(top_urls(&engine, "tomatoes"), vec!["https://b.test/"]);
+    assert!(top_urls(&engine, "spaceship").is_empty());
+}
+
+#[test]
+fn skips_pages_that_should_not_be_indexed() {
+    let mut hidden = page("https://hidden.test/", "Hidden", "secret ownership notes", &[]);
+    hidden.no_index = true;
+    let engine = memory_searcher(vec![page("https://a.test/", "Ownership", "ownership rules", &[]), hidden]);
+
+    assert_eq!(engine.searcher().num_docs(), 1);
+    assert_eq!(top_urls(&engine, "ownership"), vec!["https://a.test/"]);
+}
+
+#[test]
+fn ranks_partial_pages_below_full_ones() {
+    let mut teaser = page("https://paywalled.test/", "Ownership", "ownership rules teaser", &[]);
+    teaser.is_partial = true;
+    let engine = memory_searcher(vec![teaser, page("https://open.test/", "Ownership", "ownership rules guide", &[])]);
+
+    assert_eq!(top_urls(&engine, "ownership"), vec!["https://open.test/", "https://paywalled.test/"]);
+}
+
+#[test]
+fn title_matches_outrank_body_matches() {
+    let pages = || {
+        vec![
+            page("https://body.test/", "Rust guide", "ownership", &[]),
+            page("https://title.test/", "Ownership", "rules for moving values between variables", &[]),
+        ]
+    };
+    assert_eq!(top_urls(&memory_searcher(pages()), "ownership"), vec!["https://title.test/", "https://body.test/"]);
+
+    // Tuned BM25 parameters rescore every match and keep the boosts
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(pages(), &IndexBackend::Memory, &config).expect("in-memory indexing succeeds");
+    let search_config = SearchConfig { bm25: Bm25Params { k1: 0.9, b: 0.4 }, ..Default::default() };
+    let engine = Searcher::from_index(index, &search_config).expect("searcher opens");
+    assert_eq!(top_urls(&engine, "ownership"), vec!["https://title.test/", "https://body.test/"]);
+}
+
+#[test]
+fn keeps_one_copy_of_duplicate_content() {
+    // `https://b.test/` is linked to and so outranks its mirror `https://a.test/`
+    let engine = memory_searcher(vec![
+        page("https://a.test/", "Mirror", "Ownership   rules of Rust", &[]),
+        page("https://b.test/", "Original", "ownership rules of rust", &[]),
+        page("https://c.test/", "Links", "a page about links", &["https://b.test/"]),
+    ]);
+
+    assert_eq!(engine.searcher().num_docs(), 2);
+    assert_eq!(top_urls(&engine, "ownership"), vec!["https://b.test/"]);
+}
+
+#[test]
+fn ranks_pages_for_words_only_in_inbound_anchors() {
+    let mut linker = page("https://blog.test/", "Blog", "see this page for details", &["https://docs.test/"]);
+    linker.anchors = vec![("https://docs.test/".to_string(), "borrow checker guide".to_string())];
+    let engine = memory_searcher(vec![linker, page("https://docs.test/", "Docs", "ownership and lifetimes", &[])]);
+
+    assert_eq!(top_urls(&engine, "checker"), vec!["https://docs.test/"]);
+}
+
+#[test]
+fn recrawl_seeds_include_indexed_urls_once() {
+    let engine = memory_searcher(vec![
+        page("https://a.test/", "Ownership", "ownership rules", &[]),
+        page("https://b.test/guide", "Garden", "watering tomatoes", &[]),
+    ]);
+    let stored = stored_urls(engine.index()).expect("urls are stored");
+    assert_eq!(stored, vec!["https://a.test/", "https://b.test/guide"]);
+
+    // Seeds as typed by hand are brought into the scraper's link form before deduplication
+    let seeds = vec!["HTTPS://A.test".to_string(), "https://c.test/#top".to_string()];
+    assert_eq!(merge_seeds(&seeds, &stored), vec!["https://a.test/", "https://c.test/", "https://b.test/guide"]);
+}
+
+#[test]
+fn caps_results_per_domain_in_score_order() {
+    let hits = vec!["https://a.test/1", "https://www.a.test/2", "https://b.test/", "https://a.test/3", "https://c.test/"];
+    let capped = cap_per_domain(hits, 2, 10, |url| url.to_string());
+
+    assert_eq!(capped.hits, vec!["https://a.test/1", "https://www.a.test/2", "https://b.test/", "https://c.test/"]);
+    assert_eq!(capped.hidden, vec![("a.test".to_string(), 1)]);
+    assert_eq!(cap_per_domain(vec!["https://a.test/1", "https://b.test/"], 1, 1, |url| url.to_string()).hits.len(), 1);
+}
+
+#[test]
+fn duplicate_urls_from_several_crawls_are_shown_once() {
+    let engine = memory_searcher(vec![
+        page("https://a.test/guide", "Ownership guide", "ownership ownership ownership rules", &[]),
+        page("http://www.a.test/guide/", "Ownership guide", "ownership ownership rules, older crawl", &[]),
+        page("https://a.test/guide?utm_source=feed", "Ownership guide", "ownership rules from the feed", &[]),
+        page("https://a.test/guide?page=2", "Ownership guide, part 2", "ownership continued", &[]),
+        page("https://b.test/", "Borrowing", "ownership and borrowing", &[]),
+    ]);
+    let urls = top_urls(&engine, "ownership");
+    assert_eq!(urls.len(), 5);
+
+    let unique = dedup_by_url(urls, 10, |url| url.clone());
+    assert_eq!(unique.len(), 3);
+    assert_eq!(unique[0], "https://a.test/guide");
+    assert!(unique.contains(&"https://a.test/guide?page=2".to_string()));
+    assert_eq!(dedup_by_url(unique, 2, |url| url.clone()).len(), 2);
+}
+
+#[test]
+fn show_all_from_a_domain_restricts_the_query() {
+    let engine = memory_searcher(vec![
+        page("https://a.test/1", "Ownership", "ownership rules", &[]),
+        page("https://www.a.test/2", "Ownership", "more ownership rules", &[]),
+        page("https://nota.test/", "Ownership", "ownership elsewhere", &[]),
+    ]);
+    let searcher = engine.searcher();
+    let query = engine.parse_query("ownership").expect("query parses");
+    let query = restrict_to_domain(query, engine.fields().url, "a.test").expect("domain is valid");
+
+    assert_eq!(searcher.search(&query, &tantivy::collector::Count).expect("search succeeds"), 2);
+}
+
+#[test]
+fn empty_index_opens_and_finds_nothing() {
+    let engine = memory_searcher(Vec::new());
+
+    assert_eq!(engine.num_docs(), 0);
+    assert!(top_urls(&engine, "ownership").is_empty());
+    assert!(!engine.has_pagerank());
+    assert!(engine.suggest("own", 5).is_empty());
+}
+
+/// An in-memory index with only the first `fields` fields of the current schema, like
+/// one built by an earlier version.
+fn index_with_fields(fields: usize) -> Index {
+    let (current, _) = WebpageSchema::build();
+    let mut builder = Schema::builder();
+    for (_, entry) in current.fields().take(fields) {
+        builder.add_field(entry.clone());
+    }
+    Index::create_in_ram(builder.build())
+}
+
+#[test]
+fn older_schemas_are_searched_without_the_missing_fields() {
+    // url, title, body, pagerank and language: the schema of the first released indexes
+    let index = index_with_fields(5);
+    let schema = index.schema();
+    let field = |name| schema.get_field(name).expect("field exists");
+    let mut writer = index.writer(15_000_000).expect("writer opens");
+    writer
+        .add_document(doc!(
+            field("url") => "https://a.test/",
+            field("title") => "Ownership",
+            field("body") => "ownership rules",
+            field("pagerank") => 0.5,
+            field("language") => "eng",
+        ))
+        .expect("document is added");
+    writer.commit().expect("commit succeeds");
+
+    let engine = Searcher::from_index(index, &SearchConfig::default()).expect("older schema opens");
+    assert!(!engine.has_field("crawled_at"));
+    assert_eq!(top_urls(&engine, "ownership"), vec!["https://a.test/"]);
+    let error = engine.parse_query("ownership words:>5").expect_err("word count filter needs the field");
+    assert!(error.contains("word_count"), "{}", error);
+}
+
+#[test]
+fn indexes_without_a_body_are_rejected() {
+    assert!(Searcher::from_index(index_with_fields(2), &SearchConfig::default()).is_err());
+}
+
+#[test]
+fn language_normalization_lifts_the_best_match_of_each_language() {
+    let mut german = page("https://de.test/", "Rust Handbuch", "rust eine lange einleitung in die sprache mit vielen worten", &[]);
+    german.language = "deu".to_string();
+    let pages = vec![
+        page("https://a.test/", "Rust", "rust rust rust", &[]),
+        page("https://b.test/", "Rust", "rust rust guide", &[]),
+        german,
+    ];
+    assert_eq!(top_urls(&memory_searcher(pages.clone()), "rust").last().map(String::as_str), Some("https://de.test/"));
+
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(pages, &IndexBackend::Memory, &config).expect("in-memory indexing succeeds");
+    let search = SearchConfig { normalize_by_language: true, ..SearchConfig::default() };
+    let engine = Searcher::from_index(index, &search).expect("searcher opens");
+    let urls = top_urls(&engine, "rust");
+    assert_eq!(urls.len(), 3);
+    assert!(urls[..2].contains(&"https://de.test/".to_string()), "{:?}", urls);
+}
+
+#[test]
+fn queries_during_incremental_commits_see_whole_batches() {
+    let engine = memory_searcher(vec![page("https://a.test/", "Ownership", "ownership rules", &[])]);
+    let index = engine.index().clone();
+    let fields = engine.fields();
+    let query = engine.parse_query("ownership").expect("query parses");
+    const BATCHES: usize = 5;
+    const BATCH_SIZE: usize = 20;
+
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(move || {
+            let mut writer: tantivy::IndexWriter = index.writer(15_000_000).expect("writer opens");
+            for batch in 0..BATCHES {
+                for n in 0..BATCH_SIZE {
+                    let url = format!("https://b.test/{}/{}", batch, n);
+                    writer.add_document(doc!(fields.url => url, fields.body => "more ownership rules")).expect("document added");
+                }
+                writer.commit().expect("commit succeeds");
+            }
+        });
+
+        let mut last = 0;
+        while !writer.is_finished() {
+            engine.reload().expect("reader reloads");
+            let searcher = engine.searcher();
+            let docs = searcher.num_docs();
+            // A snapshot holds whole commits: the first page plus complete batches, all matching
+            assert_eq!(docs as usize % BATCH_SIZE, 1);
+            assert_eq!(searcher.search(&query, &tantivy::collector::Count).expect("search succeeds") as u64, docs);
+            assert!(docs >= last);
+            last = docs;
+        }
+        writer.join().expect("writer doesn't panic");
+    });
+
+    engine.reload().expect("reader reloads");
+    assert_eq!(engine.num_docs() as usize, 1 + BATCHES * BATCH_SIZE);
+}
+
+#[test]
+fn term_vectors_count_the_terms_of_the_body() {
+    let body = "Ownership moves values. Borrowing lends them, and ownership rules keep borrowing safe. Ownership!";
+    let engine = memory_searcher(vec![
+        page("https://a.test/", "Ownership", body, &[]),
+        page("https://b.test/", "Garden", "watering tomatoes", &[]),
+    ]);
+
+    let terms = engine.term_vector("https://a.test/").expect("terms are read");
+    assert_eq!(terms[..2], [("ownership".to_string(), 3), ("borrow".to_string(), 2)]);
+
+    // Exactly the tokens the indexer made of the body, with their counts
+    let mut analyzer = engine.index().tokenizers().get("en_stem").expect("tokenizer registered");
+    let mut expected: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+    let mut stream = analyzer.token_stream(body);
+    while stream.advance() {
+        *expected.entry(stream.token().text.clone()).or_insert(0) += 1;
+    }
+    assert_eq!(terms.iter().cloned().collect::<std::collections::HashMap<_, _>>(), expected);
+    assert!(!terms.iter().any(|(term, _)| term == "tomato"));
+
+    assert!(engine.term_vector("https://missing.test/").expect("lookup succeeds").is_empty());
+}
+
+#[test]
+fn projections_output_only_the_selected_fields() {
+    let engine = memory_searcher(vec![page("https://a.test/", "Ownership", "ownership rules", &[])]);
+    let schema = engine.index().schema();
+    let searcher = engine.searcher();
+    let query = engine.parse_query("ownership").expect("query parses");
+    let (score, address) = engine.top_by_relevance(&searcher, &*query, &[], 1).expect("search succeeds")[0];
+    let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+
+    let projection = Projection::parse("language, url,score", &schema).expect("fields exist");
+    let line = projection.to_json_line(&doc, Some(score));
+    assert!(line.starts_with(r#"{"language":"eng","url":"https://a.test/","score":"#), "{}", line);
+    let value: serde_json::Value = serde_json::from_str(&line).expect("valid JSON");
+    assert_eq!(value.as_object().map(|o| o.len()), Some(3));
+
+    // Unstored and unknown fields are rejected up front, listing what's available
+    let err = Projection::parse("url,body", &schema).expect_err("body isn't stored");
+    assert!(err.contains("'body'") && err.contains("title"), "{}", err);
+    assert!(Projection::parse("url,colour", &schema).is_err());
+    assert!(Projection::parse(" , ", &schema).is_err());
+}
+
+#[test]
+fn phrase_slop_allows_words_a_few_positions_apart() {
+    let engine = memory_searcher(vec![
+        page("https://a.example/gap", "Notes", "machine vision and learning systems", &[]),
+        page("https://b.example/exact", "Notes", "practical machine learning for beginners", &[]),
+    ]);
+
+    // "machine" and "learning" are two positions apart on the first page
+    assert_eq!(top_urls(&engine, "\"machine learning\"~0"), vec!["https://b.example/exact"]);
+    let mut near = top_urls(&engine, "\"machine learning\"~2");
+    near.sort_unstable();
+    assert_eq!(near, vec!["https://a.example/gap", "https://b.example/exact"]);
+    // The slop applies within each searched field, together with the other clauses
+    assert_eq!(top_urls(&engine, "\"machine learning\"~2 +vision"), vec!["https://a.example/gap"]);
+
+    // The slop isn't a query word of its own
+    let mut analyzer = engine.index().tokenizers().get("en_stem").expect("tokenizer registered");
+    let words: Vec<String> = query_words(&mut analyzer, "\"machine learning\"~2").into_iter().map(|w| w.word).collect();
+    assert_eq!(words, vec!["machine", "learning"]);
+}
+
+#[test]
+fn compaction_merges_segments_and_drops_deleted_documents() {
+    let pages = (0..6).map(|n| page(&format!("https://example.com/{}", n), "Page", &format!("page number {} text", n), &[])).collect();
+    // A commit every two pages leaves three segments
+    let config = IndexerConfig { min_words: 1, commit_every: 2, show_progress: false, ..Default::default() };
+    let index = index_pages(pages, &IndexBackend::Memory, &config).expect("in-memory indexing succeeds");
+    let url = index.schema().get_field("url").expect("url field");
+    let mut writer: tantivy::IndexWriter = index.writer(15_000_000).expect("writer opens");
+    writer.delete_term(tantivy::Term::from_field_text(url, "https://example.com/2"));
+    writer.commit().expect("delete commits");
+    drop(writer);
+
+    let usage = IndexUsage::of(&index).expect("usage is measured");
+    assert_eq!((usage.segments, usage.docs, usage.deleted_docs), (3, 5, 1));
+
+    let (before, after) = compact(&index).expect("compaction succeeds");
+    assert_eq!(before, usage);
+    assert_eq!((after.segments, after.docs, after.deleted_docs), (1, 5, 0));
+    assert!(after.files < before.files, "files of merged segments are removed");
+    // A single clean segment is left alone
+    assert_eq!(compact(&index).expect("compaction succeeds").1.segments, 1);
+}
+
+#[test]
+fn documents_store_the_time_their_page_was_fetched() {
+    let mut cached = page("https://a.test/", "Ownership", "ownership rules", &[]);
+    cached.fetched_at = Some(1_700_000_000);
+    let engine = memory_searcher(vec![cached, page("https://b.test/", "Ownership", "more ownership rules", &[])]);
+    let searcher = engine.searcher();
+    let crawled_at = |url: &str| {
+        let query = tantivy::query::TermQuery::new(
+            tantivy::Term::from_field_text(engine.fields().url, url),
+            tantivy::schema::IndexRecordOption::Basic,
+        );
+        let (_, address) = searcher.search(&query, &tantivy::collector::TopDocs::with_limit(1)).expect("search succeeds")[0];
+        let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+        doc.get_first(engine.fields().crawled_at).and_then(|v| v.as_i64()).expect("crawled_at is stored")
+    };
+
+    assert_eq!(crawled_at("https://a.test/"), 1_700_000_000);
+    // Pages without a fetch time are stamped with the time of indexing
+    assert!(crawled_at("https://b.test/") > 1_700_000_000);
+}
+
+#[test]
+fn hyphenated_terms_are_found_whole_and_by_their_parts() {
+    let engine = memory_searcher(vec![
+        page("https://a.test/gpt", "Models", "benchmarks of gpt-4 against older models", &[]),
+        page("https://b.test/covid", "Health", "weekly covid-19 case numbers", &[]),
+        page("https://c.test/other", "Models", "gpt 3 and 4 compared", &[]),
+    ]);
+    // The index splits on hyphens, so "gpt-4" is the phrase "gpt 4"
+    assert_eq!(top_urls(&engine, "gpt-4"), ["https://a.test/gpt"]);
+    assert_eq!(top_urls(&engine, "covid"), ["https://b.test/covid"]);
+    assert_eq!(top_urls(&engine, "covid-19"), ["https://b.test/covid"]);
+}
+
+#[test]
+fn malformed_phrase_and_operator_queries_are_explained() {
+    assert!(validate_query("\"machine learning\" +rust -java").is_ok());
+    assert!(validate_query("\"machine learning").expect_err("open phrase").contains("Unbalanced quotes"));
+    for query in ["rust +", "rust -", "- rust"] {
+        let error = validate_query(query).expect_err("dangling operator");
+        assert!(error.contains("must be followed by a term"), "{}: {}", query, error);
+    }
+    assert!(validate_query("-java -python").expect_err("only exclusions").contains("only excluded terms"));
+}
+
+#[test]
+fn phrases_match_adjacent_words_and_exclusions_drop_pages() {
+    let engine = memory_searcher(vec![
+        page("https://a.test/apart", "Notes", "machine vision and learning systems", &[]),
+        page("https://b.test/phrase", "Notes", "practical machine learning in rust", &[]),
+        page("https://c.test/java", "Notes", "machine learning with java and rust", &[]),
+    ]);
+
+    let mut phrase = top_urls(&engine, "\"machine learning\"");
+    phrase.sort_unstable();
+    assert_eq!(phrase, vec!["https://b.test/phrase", "https://c.test/java"]);
+    assert_eq!(top_urls(&engine, "\"machine learning\" -java"), vec!["https://b.test/phrase"]);
+    assert_eq!(top_urls(&engine, "+vision learning"), vec!["https://a.test/apart"]);
+}
+
+#[test]
+fn stopwords_are_not_indexed() {
+    let body = "the cat and the dog with a bone";
+    let engine = memory_searcher(vec![page("https://a.test/", "Pets", body, &[])]);
+    let terms: Vec<String> = engine.term_vector("https://a.test/").expect("terms are read").into_iter().map(|(t, _)| t).collect();
+    for stopword in ["the", "and", "with", "a"] {
+        assert!(!terms.contains(&stopword.to_string()), "{} is indexed: {:?}", stopword, terms);
+    }
+    assert!(terms.contains(&"cat".to_string()) && terms.contains(&"bone".to_string()));
+
+    // A custom list replaces the default one
+    let path = std::env::temp_dir().join(format!("search_enginge_stopwords_{}.txt", std::process::id()));
+    std::fs::write(&path, "# pets\ncat\ndog\n").expect("stopword file is written");
+    let stopwords = Stopwords::from_file(&path).expect("stopword file is read");
+    std::fs::remove_file(&path).ok();
+    let config = IndexerConfig { min_words: 1, show_progress: false, stopwords: stopwords.clone(), ..Default::default() };
+    let index = index_pages(vec![page("https://a.test/", "Pets", body, &[])], &IndexBackend::Memory, &config).expect("in-memory indexing succeeds");
+    let engine = Searcher::from_index(index, &SearchConfig { stopwords, ..SearchConfig::default() }).expect("searcher opens");
+    let terms: Vec<String> = engine.term_vector("https://a.test/").expect("terms are read").into_iter().map(|(t, _)| t).collect();
+    assert!(!terms.contains(&"cat".to_string()) && !terms.contains(&"dog".to_string()), "{:?}", terms);
+    assert!(terms.contains(&"the".to_string()) && terms.contains(&"with".to_string()), "{:?}", terms);
+}
+
+/// Panics on the first "Committed ..." log line, like a crash right after the first batch.
+struct CrashAfterFirstCommit;
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for CrashAfterFirstCommit {
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        let mut message = String::new();
+        event.record(&mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+            if field.name() == "message" {
+                message = format!("{:?}", value);
+            }
+        });
+        if message.starts_with("Committed") {
+            panic!("simulated crash after the first commit");
+        }
+    }
+}
+
+#[test]
+fn an_interrupted_run_keeps_the_previous_index() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let dir = std::env::temp_dir().join(format!("search_enginge_interrupted_{}", std::process::id()));
+    let backend = IndexBackend::Disk(dir.clone());
+    let pages = |title: &str, n: usize| -> Vec<ScrapeResult> {
+        (0..n).map(|i| page(&format!("https://a.test/{}", i), title, &format!("{} page number {}", title, i), &[])).collect()
+    };
+    let titles = || -> Vec<String> {
+        let index = Index::open_in_dir(&dir).expect("index reopens");
+        let title = index.schema().get_field("title").expect("title field");
+        let searcher = index.reader().expect("reader opens").searcher();
+        let mut titles: Vec<String> = searcher
+            .search(&tantivy::query::AllQuery, &tantivy::collector::DocSetCollector)
+            .expect("search succeeds")
+            .into_iter()
+            .map(|address| {
+                let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+                doc.get_first(title).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+            })
+            .collect();
+        titles.sort_unstable();
+        titles
+    };
+    let config = IndexerConfig { min_words: 1, commit_every: 2, show_progress: false, ..Default::default() };
+    index_pages(pages("old", 6), &backend, &config).expect("first run succeeds");
+
+    let subscriber = tracing_subscriber::registry().with(CrashAfterFirstCommit);
+    let crashed = tracing::subscriber::with_default(subscriber, || {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| index_pages(pages("new", 6), &backend, &config)))
+    });
+    assert!(crashed.is_err(), "the run is interrupted");
+    // The first batch replaced its pages, the rest of the previous index survived
+    assert_eq!(titles(), ["new", "new", "old", "old", "old", "old"]);
+
+    // A complete run drops the pages it didn't index again
+    index_pages(pages("new", 4), &backend, &config).expect("rerun succeeds");
+    assert_eq!(titles(), ["new", "new", "new", "new"]);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn pages_redirected_to_one_url_leave_one_document() {
+    // Every request ended on the same page, e.g. old URLs all redirecting to `/`
+    let pages: Vec<ScrapeResult> =
+        (0..64).map(|i| page("https://a.test/", &format!("Copy {}", i), &format!("the page as fetched time {}", i), &[])).collect();
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let index = index_pages(pages, &IndexBackend::Memory, &config).expect("in-memory indexing succeeds");
+    let title = index.schema().get_field("title").expect("title field");
+    let searcher = index.reader().expect("reader opens").searcher();
+    let addresses = searcher.search(&tantivy::query::AllQuery, &tantivy::collector::DocSetCollector).expect("search succeeds");
+    let titles: Vec<String> = addresses
+        .into_iter()
+        .map(|address| {
+            let doc: TantivyDocument = searcher.doc(address).expect("stored doc");
+            doc.get_first(title).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+        })
+        .collect();
+    // The last one fetched, as a later batch would have replaced the earlier ones
+    assert_eq!(titles, ["Copy 63"]);
+}
+
+#[test]
+fn an_index_with_another_schema_is_left_alone() {
+    let dir = std::env::temp_dir().join(format!("search_enginge_other_schema_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("dir is created");
+    let mut builder = tantivy::schema::Schema::builder();
+    builder.add_text_field("notes", tantivy::schema::TEXT);
+    Index::create_in_dir(&dir, builder.build()).expect("other index is created");
+
+    let config = IndexerConfig { min_words: 1, show_progress: false, ..Default::default() };
+    let pages = vec![page("https://a.test/", "Ownership", "ownership rules", &[])];
+    let error = index_pages(pages, &IndexBackend::Disk(dir.clone()), &config).expect_err("schemas differ");
+    assert!(error.to_string().contains("incompatible schema"), "{}", error);
+    let kept = Index::open_in_dir(&dir).expect("the other index is still there");
+    assert!(kept.schema().get_field("notes").is_ok());
+    std::fs::remove_dir_all(&dir).ok();
+}
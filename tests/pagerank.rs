@@ -0,0 +1,125 @@
+//! PageRank on small graphs whose ranks can be worked out by hand (damping 0.85).
+
+use search_enginge::crawler::datascraper::is_internal_link;
+use search_enginge::indexer::algorithms::pagerank::{
+    calculate_pagerank, calculate_pagerank_with, edges, LinkGraph, PageRankConfig, PageRanks,
+};
+
+/// Iteration stops once the total change drops below 1e-4, so ranks are only that close.
+const TOLERANCE: f64 = 1e-3;
+
+fn graph(edges: &[(&str, &[&str])]) -> LinkGraph {
+    edges
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.iter().map(|t| t.to_string()).collect()))
+        .collect()
+}
+
+fn assert_rank(ranks: &PageRanks, url: &str, expected: f64) {
+    let rank = ranks[url];
+    assert!((rank - expected).abs() < TOLERANCE, "rank of {} is {}, expected {}", url, rank, expected);
+}
+
+fn assert_sums_to_one(ranks: &PageRanks) {
+    let total: f64 = ranks.values().sum();
+    assert!((total - 1.0).abs() < TOLERANCE, "ranks sum to {}", total);
+}
+
+#[test]
+fn two_node_cycle_ranks_equally() {
+    let ranks = calculate_pagerank(&graph(&[("a", &["b"]), ("b", &["a"])]));
+
+    assert_eq!(ranks.len(), 2);
+    assert_rank(&ranks, "a", 0.5);
+    assert_rank(&ranks, "b", 0.5);
+    assert_sums_to_one(&ranks);
+}
+
+#[test]
+fn star_center_dominates() {
+    // Four leaves link to a center without out-links. With c the center's rank and l a
+    // leaf's: l = 0.15/5 + 0.85c/5 and c + 4l = 1, so c = 0.88/1.68 and l = (1 - c)/4.
+    let ranks = calculate_pagerank(&graph(&[
+        ("l1", &["center"]),
+        ("l2", &["center"]),
+        ("l3", &["center"]),
+        ("l4", &["center"]),
+        ("center", &[]),
+    ]));
+
+    let center = 0.88 / 1.68;
+    assert_rank(&ranks, "center", center);
+    for leaf in ["l1", "l2", "l3", "l4"] {
+        assert_rank(&ranks, leaf, (1.0 - center) / 4.0);
+        assert!(ranks["center"] > ranks[leaf]);
+    }
+    assert_sums_to_one(&ranks);
+}
+
+#[test]
+fn dangling_mass_is_redistributed() {
+    // `c` is only a link target, so it has no out-links and its rank is spread over all
+    // pages. With base = 0.05 + 0.85c/3: a = base, b = 1.425 base, c = 2.63625 base.
+    let ranks = calculate_pagerank(&graph(&[("a", &["b", "c"]), ("b", &["c"])]));
+
+    let base = 1.0 / (1.0 + 1.425 + 2.63625);
+    assert_eq!(ranks.len(), 3);
+    assert_rank(&ranks, "a", base);
+    assert_rank(&ranks, "b", 1.425 * base);
+    assert_rank(&ranks, "c", 2.63625 * base);
+    assert_sums_to_one(&ranks);
+}
+
+#[test]
+fn empty_graph_has_no_ranks() {
+    assert!(calculate_pagerank(&LinkGraph::new()).is_empty());
+}
+
+#[test]
+fn links_on_the_same_host_are_internal() {
+    assert!(is_internal_link("https://example.com/a", "https://example.com/b?page=2"));
+    assert!(is_internal_link("https://example.com/a", "http://EXAMPLE.com/b"));
+    assert!(is_internal_link("https://www.example.com/", "https://example.com/about"));
+}
+
+#[test]
+fn links_to_other_hosts_are_external() {
+    assert!(!is_internal_link("https://example.com/", "https://other.org/"));
+    assert!(!is_internal_link("https://example.com/", "https://blog.example.com/"));
+    assert!(!is_internal_link("https://example.com/", "not a url"));
+}
+
+#[test]
+fn edges_are_tagged_by_host() {
+    let graph = graph(&[("https://a.com/", &["https://a.com/about", "https://b.com/"])]);
+    let mut tagged: Vec<(&str, bool)> = edges(&graph).map(|e| (e.to, e.is_internal)).collect();
+    tagged.sort();
+
+    assert_eq!(tagged, vec![("https://a.com/about", true), ("https://b.com/", false)]);
+}
+
+#[test]
+fn discounting_internal_links_favors_external_targets() {
+    // The home page links to two of its own pages and one page on another site
+    let graph = graph(&[
+        ("https://a.com/", &["https://a.com/1", "https://a.com/2", "https://b.com/"]),
+        ("https://a.com/1", &["https://a.com/"]),
+        ("https://a.com/2", &["https://a.com/"]),
+        ("https://b.com/", &[]),
+    ]);
+    let (classic, _) = calculate_pagerank_with(&graph, &PageRankConfig::default());
+    let config = PageRankConfig { internal_link_weight: 0.1, ..PageRankConfig::default() };
+    let (discounted, _) = calculate_pagerank_with(&graph, &config);
+
+    assert!(discounted["https://b.com/"] > classic["https://b.com/"]);
+    assert!(discounted["https://a.com/1"] < classic["https://a.com/1"]);
+    assert_sums_to_one(&discounted);
+}
+
+#[test]
+fn full_internal_link_weight_is_classic_pagerank() {
+    let graph = graph(&[("https://a.com/", &["https://a.com/1", "https://b.com/"]), ("https://a.com/1", &["https://a.com/"])]);
+    let (weighted, _) = calculate_pagerank_with(&graph, &PageRankConfig { internal_link_weight: 1.0, ..PageRankConfig::default() });
+
+    assert_eq!(weighted, calculate_pagerank(&graph));
+}
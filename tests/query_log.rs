@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use search_enginge::searcher::querylog::{QueryLog, QueryReport};
+
+#[test]
+fn reports_aggregate_frequent_and_zero_result_queries() {
+    let path = std::env::temp_dir().join(format!("search_enginge_queries_{}.ndjson", std::process::id()));
+    std::fs::remove_file(&path).ok();
+
+    let log = QueryLog::open(&path).expect("log opens");
+    for (query, results, ms) in [
+        ("rust async", 10, 4),
+        ("Rust  Async", 10, 6),
+        ("tomatoes", 3, 2),
+        ("quantum gardening", 0, 1),
+        ("rust async", 9, 3),
+        ("quantum gardening", 0, 2),
+        ("zeppelin", 0, 2),
+    ] {
+        log.record(query, results, Duration::from_millis(ms)).expect("entry is written");
+    }
+    // Reopening appends instead of truncating
+    QueryLog::open(&path).expect("log reopens").record("tomatoes", 3, Duration::from_millis(4)).expect("entry is written");
+
+    let report = QueryReport::from_log(&path, 2).expect("log is read");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(report.total_queries, 8);
+    assert_eq!(report.distinct_queries, 4);
+    assert_eq!(report.zero_result_queries, 3);
+    assert_eq!(report.average_latency_ms, 3.0);
+    assert_eq!(report.popular, vec![("rust async".to_string(), 3), ("quantum gardening".to_string(), 2)]);
+    assert_eq!(report.zero_results, vec![("quantum gardening".to_string(), 2), ("zeppelin".to_string(), 1)]);
+}
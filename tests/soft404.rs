@@ -0,0 +1,61 @@
+use search_enginge::crawler::soft404::Soft404Detector;
+use url::Url;
+
+fn url(raw: &str) -> Url {
+    Url::parse(raw).expect("valid URL")
+}
+
+fn long_article(extra: &str) -> String {
+    let filler = "Gardeners water tomatoes early in the morning so the leaves dry before the heat of the day. ";
+    format!("{}{}", filler.repeat(12), extra)
+}
+
+#[test]
+fn error_titles_are_flagged_regardless_of_the_body() {
+    let detector = Soft404Detector::default();
+    let page = url("https://a.test/missing");
+    for title in ["404 - Page Not Found", "Oops! That page doesn't exist", "Product no longer available"] {
+        assert!(detector.is_soft_404(&page, &page, Some(title), &long_article("")), "{}", title);
+    }
+    assert!(!detector.is_soft_404(&page, &page, Some("Growing tomatoes"), &long_article("")));
+}
+
+#[test]
+fn error_wording_only_counts_on_short_pages() {
+    let detector = Soft404Detector::default();
+    let page = url("https://a.test/missing");
+    assert!(detector.is_soft_404(&page, &page, Some("Oops"), "Sorry, the page you are looking for could not be found."));
+    assert!(detector.is_soft_404(&page, &page, None, "Error 404. Go back home."));
+    // A long article mentioning the same words in passing is a real page
+    let article = long_article("The variety you asked about could not be found in any local shop.");
+    assert!(!detector.is_soft_404(&page, &page, Some("Tomato varieties"), &article));
+}
+
+#[test]
+fn short_pages_redirected_to_the_homepage_are_flagged() {
+    let detector = Soft404Detector::default();
+    let home = url("https://a.test/");
+    let short = "Welcome to our shop.";
+    assert!(detector.is_soft_404(&url("https://a.test/old/post"), &home, Some("Shop"), short));
+    // Not when the homepage has real content, was asked for, or is another site's
+    assert!(!detector.is_soft_404(&url("https://a.test/old/post"), &home, Some("Shop"), &long_article("")));
+    assert!(!detector.is_soft_404(&home, &home, Some("Shop"), short));
+    assert!(!detector.is_soft_404(&url("https://a.test/old/post"), &url("https://b.test/"), Some("Shop"), short));
+}
+
+#[test]
+fn patterns_are_configurable() {
+    let german = Soft404Detector {
+        title_patterns: vec!["Seite nicht gefunden".to_string()],
+        body_patterns: vec!["existiert nicht".to_string()],
+        ..Soft404Detector::default()
+    };
+    let page = url("https://a.test/fehlt");
+    assert!(german.is_soft_404(&page, &page, Some("Fehler: SEITE NICHT GEFUNDEN"), &long_article("")));
+    assert!(german.is_soft_404(&page, &page, Some("Fehler"), "Diese Seite existiert nicht."));
+    // The default English patterns are replaced, not extended
+    assert!(!german.is_soft_404(&page, &page, Some("Page not found"), &long_article("")));
+
+    let disabled = Soft404Detector::disabled();
+    assert!(!disabled.is_soft_404(&page, &url("https://a.test/"), Some("404 Not Found"), "page not found"));
+}
@@ -0,0 +1,103 @@
+use search_enginge::crawler::datascraper::{count_word_ids, count_words, count_words_with};
+use search_enginge::text::interner::{CorpusWordCounts, SharedInterner, WordCounts, WordInterner};
+use search_enginge::text::normalize::{words, Normalization, WordMode, WordOptions};
+use search_enginge::text::Stopwords;
+
+const UNICODE_NFC: WordOptions = WordOptions { normalization: Normalization::Nfc, mode: WordMode::Unicode };
+
+#[test]
+fn default_options_keep_the_previous_splitting() {
+    assert_eq!(words("Covid-19 and GPT-4 -- foo--bar, 5g a_b", WordOptions::default()), [
+        "covid-19", "and", "gpt-4", "foo", "bar", "5g"
+    ]);
+    let counts = count_words("Rust rust RUST the", &Stopwords::english());
+    assert_eq!(counts.get("rust"), Some(&3));
+    assert_eq!(counts.get("the"), None);
+}
+
+#[test]
+fn technical_terms_are_counted_whole() {
+    let counts = count_words("GPT-4 and gpt-4, covid-19 cases, rust2024 over 5g", &Stopwords::english());
+    assert_eq!(counts.get("gpt-4"), Some(&2));
+    assert_eq!(counts.get("covid-19"), Some(&1));
+    assert_eq!(counts.get("rust2024"), Some(&1));
+    assert_eq!(counts.get("5g"), Some(&1));
+    for mangled in ["gpt", "covid", "rust"] {
+        assert_eq!(counts.get(mangled), None, "{}", mangled);
+    }
+}
+
+#[test]
+fn nfc_merges_precomposed_and_decomposed_accents() {
+    let text = "café cafe\u{301} Crème cre\u{300}me";
+    // By default the combining accent ends the word: the accent is lost and "me" is too short
+    assert_eq!(words(text, WordOptions::default()), ["café", "cafe", "crème", "cre"]);
+
+    let counts = count_words_with(text, &Stopwords::none(), UNICODE_NFC);
+    assert_eq!(counts.get("café"), Some(&2));
+    assert_eq!(counts.get("crème"), Some(&2));
+    assert_eq!(counts.len(), 2);
+}
+
+#[test]
+fn unicode_mode_keeps_combining_marks_without_normalizing() {
+    let options = WordOptions { normalization: Normalization::None, mode: WordMode::Unicode };
+    // Decomposed accents stay attached, so the word survives (still decomposed)
+    assert_eq!(words("cafe\u{301}", options), ["cafe\u{301}"]);
+    // Devanagari vowel signs and the virama are combining marks
+    assert_eq!(words("हिन्दी भाषा", options), ["हिन्दी", "भाषा"]);
+}
+
+#[test]
+fn nfkc_folds_compatibility_forms() {
+    let options = WordOptions { normalization: Normalization::Nfkc, mode: WordMode::Alphanumeric };
+    assert_eq!(words("ｒｕｓｔ ﬁle Ⅻ", options), ["rust", "file", "xii"]);
+    assert_eq!(words("ｒｕｓｔ ﬁle", WordOptions::default()), ["ｒｕｓｔ", "ﬁle"]);
+}
+
+#[test]
+fn cjk_runs_become_bigrams() {
+    assert_eq!(words("東京大学", UNICODE_NFC), ["東京", "京大", "大学"]);
+    // Kana are split like ideographs, Latin words around them stay whole
+    assert_eq!(words("Rustの本 を読む", UNICODE_NFC), ["rust", "の本", "を読", "読む"]);
+    // A lone ideograph is a word despite the 3-character minimum
+    assert_eq!(words("猫 cat", UNICODE_NFC), ["猫", "cat"]);
+    // The default mode treats a whole unspaced sentence as one word
+    assert_eq!(words("東京大学で勉強する", WordOptions::default()), ["東京大学で勉強する"]);
+}
+
+#[test]
+fn normalization_names_parse() {
+    assert_eq!("NFKC".parse::<Normalization>(), Ok(Normalization::Nfkc));
+    assert_eq!("none".parse::<Normalization>(), Ok(Normalization::None));
+    assert!("nfd".parse::<Normalization>().is_err());
+}
+
+#[test]
+fn interned_counts_match_word_counts() {
+    let text = "Rust borrows, rust owns; the borrow checker checks borrows";
+    let mut interner = WordInterner::new();
+    let ids = count_word_ids(text, &Stopwords::english(), WordOptions::default(), &mut interner);
+
+    let by_word: std::collections::HashMap<String, u32> =
+        ids.iter().map(|&(id, count)| (interner.word(id).expect("id was interned").to_string(), count)).collect();
+    assert_eq!(by_word, count_words(text, &Stopwords::english()));
+    assert_eq!(interner.id("rust"), Some(0));
+    assert_eq!(interner.id("the"), None);
+}
+
+#[test]
+fn corpus_counts_share_ids_across_pages() {
+    let mut corpus = CorpusWordCounts::new();
+    let first = corpus.add_text("ownership and borrowing", &Stopwords::english(), WordOptions::default());
+    let second = corpus.add_text("Borrowing rules, borrowing again", &Stopwords::english(), WordOptions::default());
+    // Counted with another interner, so added by word
+    corpus.add_word_counts(&WordCounts::from_map(&count_words("ownership", &Stopwords::english()), &SharedInterner::new()));
+
+    let borrowing = corpus.interner().read().id("borrowing").expect("interned");
+    assert!(first.contains(&(borrowing, 1)) && second.contains(&(borrowing, 2)));
+    assert_eq!(corpus.interner().read().len(), 4);
+    assert_eq!(corpus.total("borrowing"), 3);
+    assert_eq!(corpus.pages_containing("ownership"), 2);
+    assert_eq!(corpus.top_terms(2), [("borrowing".to_string(), 3), ("ownership".to_string(), 2)]);
+}